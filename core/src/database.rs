@@ -35,7 +35,7 @@ pub fn pages() -> Vec<Page<'static>> {
         Page {
             id: "wire-world",
             title: "Wire-World".to_string(),
-            content: "Not implemented".to_owned(),
+            content: include_str!("../pages/wire_world.txt").to_string(),
             links: vec!["experiments"]
         },
     ]
@@ -10,6 +10,7 @@ import cvText from '@/pages/cv.txt';
 import { parseContent, ParsedLink } from '@/utils/contentParser';
 import { HoverLinkInfo, transformMarkersToLinks } from '@/utils/linkOverlayBinder';
 import OldComputerFilterDefs from '@/components/OldComputerFilterDefs';
+import { startWireWorld, WIRE_WORLD_EXIT_EVENT } from '@/utils/wireWorld';
 
 type CommandResponse = string | string[] | Promise<string | string[]>;
 
@@ -29,6 +30,7 @@ const TerminalConsole = () => {
   });
   const overlayCleanupTimeout = useRef<number | null>(null);
   const [filterId, setFilterId] = useState('terminal-old-filter');
+  const [gameVisible, setGameVisible] = useState(false);
 
   const hideOverlay = useCallback(() => {
     setOverlayVisible(false);
@@ -59,6 +61,33 @@ const TerminalConsole = () => {
     return () => window.removeEventListener('keydown', handleKeydown);
   }, [overlayVisible, hideOverlay]);
 
+  useEffect(() => {
+    const handleExit = () => {
+      setGameVisible(false);
+      document.querySelector<HTMLInputElement>("[name='react-console-emulator__input']")?.focus();
+    };
+
+    window.addEventListener(WIRE_WORLD_EXIT_EVENT, handleExit);
+    return () => window.removeEventListener(WIRE_WORLD_EXIT_EVENT, handleExit);
+  }, []);
+
+  const openGame = useCallback(async (level?: string) => {
+    setGameVisible(true);
+
+    try {
+      await startWireWorld(level);
+    } catch (error) {
+      setGameVisible(false);
+      console.error(error);
+      return 'Wire-World failed to start. Is it built with `npm run build:wasm`?';
+    }
+
+    document.getElementById('render')?.focus();
+    return level
+      ? `Wire-World is running ${level}. The close button of the level menu returns here.`
+      : 'Wire-World is running. The close button of the level menu returns here.';
+  }, []);
+
   const findLink = useCallback(
     (commandId: string, marker: string): ParsedLink | undefined =>
       parsedLinksRef.current[commandId]?.find((link) => link.marker === marker),
@@ -143,8 +172,13 @@ const TerminalConsole = () => {
         description: 'Resume',
         fn: () => handleContent('cv', cvText),
       },
+      'wire-world': {
+        description: 'Play Wire-World, a level file like diode.level opens right away',
+        usage: 'wire-world [level]',
+        fn: (level?: string) => openGame(level),
+      },
     }),
-    [handleContent],
+    [handleContent, openGame],
   );
 
   return (
@@ -182,6 +216,9 @@ const TerminalConsole = () => {
           welcomeMessage={welcomeParsed.text}
         />
       </div>
+      <div className={`${styles.game} ${gameVisible ? styles.gameActive : styles.gameInactive}`}>
+        <canvas id="render" className={styles.gameCanvas} />
+      </div>
       {overlayImage && (
         <div
           className={`${styles.overlay} ${overlayVisible ? styles.overlayActive : styles.overlayInactive}`}
@@ -0,0 +1,45 @@
+type WireWorldModule = {
+  default: () => Promise<unknown>;
+  start: (level?: string) => void;
+};
+
+/** Dispatched on `window` by the game when the player closes it. */
+export const WIRE_WORLD_EXIT_EVENT = 'wire-world-exit';
+
+/** Output of `wasm-bindgen --target web`, see `npm run build:wasm`. */
+const MODULE_URL = '/wire_world/zhdanov_wire_world.js';
+
+let loading: Promise<WireWorldModule> | null = null;
+
+const loadModule = () => {
+  if (!loading) {
+    loading = import(/* webpackIgnore: true */ /* turbopackIgnore: true */ MODULE_URL)
+      .then(async (module: WireWorldModule) => {
+        await module.default();
+        return module;
+      })
+      .catch((error) => {
+        loading = null;
+        throw error;
+      });
+  }
+
+  return loading;
+};
+
+/**
+ * Starts the game in the `#render` canvas, or shows the running game again: it can't be started twice
+ * on one page. Without a level the game opens the level menu.
+ */
+export const startWireWorld = async (level?: string) => {
+  const module = await loadModule();
+
+  try {
+    module.start(level);
+  } catch (error) {
+    // The first start hands the page to the game loop by throwing an exception on purpose.
+    if (!(error instanceof Error && error.message.includes('Using exceptions for control flow'))) {
+      throw error;
+    }
+  }
+};
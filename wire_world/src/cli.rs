@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Error;
+
+use crate::world::format::converted_path;
+use crate::world::resources::World;
+use crate::world::verifier;
+
+const USAGE: &str = "Usage:
+    wire_world                         start the game
+    wire_world check <level>...        validate levels without starting a window
+    wire_world convert <level>...      write <level>.ron in the versioned RON format
+    wire_world verify <level> <board>  play exercises of the level on the board of another level file";
+
+/// Runs a command-line subcommand without starting a window and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    match args.split_first() {
+        Some((command, files)) if command == "check" && !files.is_empty() => check(files),
+        Some((command, files)) if command == "convert" && !files.is_empty() => convert(files),
+        Some((command, files)) if command == "verify" && files.len() == 2 => verify(&files[0], &files[1]),
+        _others => {
+            eprintln!("{USAGE}");
+            2
+        }
+    }
+}
+
+fn check(files: &[String]) -> i32 {
+    let mut code = 0;
+    for file in files {
+        let errors = match fs::read_to_string(file) {
+            Ok(content) => match World::from_file(Path::new(file), content) {
+                Ok(world) => world.validate().iter().map(|error| error.to_string()).collect(),
+                Err(error) => vec![error.to_string()],
+            },
+            Err(error) => vec![error.to_string()],
+        };
+
+        if errors.is_empty() {
+            println!("{file}: ok");
+        } else {
+            for error in errors {
+                eprintln!("{file}: {error}");
+            }
+            code = 1;
+        }
+    }
+    code
+}
+
+fn convert(files: &[String]) -> i32 {
+    let mut code = 0;
+    for file in files {
+        match convert_file(Path::new(file)) {
+            Ok(converted) => println!("{file} -> {converted}"),
+            Err(error) => {
+                eprintln!("{file}: {error}");
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+fn verify(level: &str, solution: &str) -> i32 {
+    match verify_files(Path::new(level), Path::new(solution)) {
+        Ok(reports) if reports.is_empty() => {
+            println!("{level}: no exercises to verify");
+            0
+        }
+        Ok(reports) => {
+            let failed = reports.iter().any(|report| !report.passed());
+            for report in reports {
+                println!("{report}");
+            }
+            match failed {
+                true => 1,
+                false => 0,
+            }
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            1
+        }
+    }
+}
+
+fn verify_files(level: &Path, solution: &Path) -> anyhow::Result<Vec<verifier::ExerciseReport>, Error> {
+    let level = World::from_file(level, fs::read_to_string(level)?)?;
+    let solution = World::from_file(solution, fs::read_to_string(solution)?)?;
+
+    Ok(verifier::verify(&level, &solution)?)
+}
+
+fn convert_file(path: &Path) -> anyhow::Result<String, Error> {
+    let world = World::from_file(path, fs::read_to_string(path)?)?;
+    let converted = converted_path(path);
+    fs::write(&converted, world.to_ron()?)?;
+
+    Ok(converted.display().to_string())
+}
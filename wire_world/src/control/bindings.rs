@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Name of the file with remapped keys, it is kept next to the assets directory.
+pub const BINDINGS_FILE: &str = "keys.ron";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum KeyAction {
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+    FitBoard,
+    /// Plays the board at the last speed or pauses it.
+    PlayPause,
+    Step,
+    SpeedNormal,
+    SpeedFast,
+    SpeedFastest,
+    Menu,
+    /// Moves the cell cursor.
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    /// Clicks the cell under the cursor like the left button.
+    ToggleCell,
+    /// Clicks the cell under the cursor like the right button, which places an electron.
+    PlaceElectron,
+    /// Moves the focus between level cards in the level menu.
+    PreviousLevel,
+    NextLevel,
+    OpenLevel,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BindingsFile {
+    pub keys: BTreeMap<KeyAction, Vec<KeyCode>>,
+}
+
+/// Keys of every action. A key file replaces keys of the actions it lists, others keep the defaults.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    pub keys: BTreeMap<KeyAction, Vec<KeyCode>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use KeyAction::*;
+
+        KeyBindings {
+            keys: BTreeMap::from([
+                (PanLeft, vec![KeyCode::Left, KeyCode::A]),
+                (PanRight, vec![KeyCode::Right, KeyCode::D]),
+                (PanUp, vec![KeyCode::Up, KeyCode::W]),
+                (PanDown, vec![KeyCode::Down, KeyCode::S]),
+                (ZoomIn, vec![KeyCode::Plus, KeyCode::Equals, KeyCode::NumpadAdd]),
+                (ZoomOut, vec![KeyCode::Minus, KeyCode::NumpadSubtract]),
+                (FitBoard, vec![KeyCode::F]),
+                (PlayPause, vec![KeyCode::Space]),
+                (Step, vec![KeyCode::Period]),
+                (SpeedNormal, vec![KeyCode::Key1]),
+                (SpeedFast, vec![KeyCode::Key2]),
+                (SpeedFastest, vec![KeyCode::Key3]),
+                (Menu, vec![KeyCode::Escape]),
+                (CursorLeft, vec![KeyCode::J]),
+                (CursorRight, vec![KeyCode::L]),
+                (CursorUp, vec![KeyCode::I]),
+                (CursorDown, vec![KeyCode::K]),
+                (ToggleCell, vec![KeyCode::Return]),
+                (PlaceElectron, vec![KeyCode::E]),
+                (PreviousLevel, vec![KeyCode::Left, KeyCode::A]),
+                (NextLevel, vec![KeyCode::Right, KeyCode::D]),
+                (OpenLevel, vec![KeyCode::Return, KeyCode::Space]),
+            ]),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: KeyAction) -> Vec<KeyCode> {
+        self.keys.get(&action).cloned().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, input: &Input<KeyCode>, action: KeyAction) -> bool {
+        input.any_pressed(self.keys(action))
+    }
+
+    pub fn just_pressed(&self, input: &Input<KeyCode>, action: KeyAction) -> bool {
+        input.any_just_pressed(self.keys(action))
+    }
+
+    pub fn just_released(&self, input: &Input<KeyCode>, action: KeyAction) -> bool {
+        input.any_just_released(self.keys(action))
+    }
+
+    pub fn from_ron(s: &str) -> anyhow::Result<KeyBindings> {
+        let file: BindingsFile = ron::from_str(s)?;
+        let mut bindings = KeyBindings::default();
+        bindings.keys.extend(file.keys);
+
+        Ok(bindings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_file_replaces_listed_actions() {
+        let bindings = KeyBindings::from_ron("(keys: { PlayPause: [P], Step: [] })").unwrap();
+
+        assert_eq!(bindings.keys(KeyAction::PlayPause), vec![KeyCode::P]);
+        assert_eq!(bindings.keys(KeyAction::Step), vec![]);
+        assert_eq!(bindings.keys(KeyAction::PanLeft), vec![KeyCode::Left, KeyCode::A]);
+        assert!(KeyBindings::from_ron("(keys: { Jump: [Space] })").is_err());
+    }
+
+    #[test]
+    fn bindings_read_input() {
+        let bindings = KeyBindings::default();
+        let mut input = Input::<KeyCode>::default();
+        input.press(KeyCode::D);
+
+        assert!(bindings.pressed(&input, KeyAction::PanRight));
+        assert!(bindings.just_pressed(&input, KeyAction::NextLevel));
+        assert!(!bindings.pressed(&input, KeyAction::PanLeft));
+        input.clear();
+        input.release(KeyCode::D);
+        assert!(bindings.just_released(&input, KeyAction::PanRight));
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+/// Limits of `OrthographicProjection::scale`, world units per pixel of the window.
+pub const MIN_ZOOM: f32 = 0.25;
+pub const MAX_ZOOM: f32 = 64.0;
+
+/// Part of the window left around the board when it is fitted.
+const FIT_MARGIN: f32 = 0.1;
+
+/// Rectangle of the current board in the world, the camera doesn't leave it.
+#[derive(Resource, Debug, Default)]
+pub struct BoardBounds(pub Option<Rect>);
+
+/// Centers the camera on the board and zooms out until the whole board is in the window.
+#[derive(Event)]
+pub struct FitBoard;
+
+/// Camera position and scale after zooming by the factor, the anchor point of the world stays
+/// under the cursor.
+pub fn zoom_at(translation: Vec2, scale: f32, anchor: Vec2, factor: f32) -> (Vec2, f32) {
+    let zoomed = (scale * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+    (anchor + (translation - anchor) * (zoomed / scale), zoomed)
+}
+
+/// Keeps the center of the window over the board, so some of the board is always on screen.
+pub fn clamp_to_board(translation: Vec2, board: Rect) -> Vec2 {
+    translation.clamp(board.min, board.max)
+}
+
+/// Camera position and scale which show the whole board in a window of the given size.
+/// Boards smaller than the window keep their cell size.
+pub fn fit(board: Rect, window: Vec2) -> (Vec2, f32) {
+    let scale = (board.size() / window).max_element() * (1.0 + FIT_MARGIN);
+    (board.center(), scale.clamp(1.0, MAX_ZOOM))
+}
+
+/// Part of the world which the camera shows in a window of the given size.
+pub fn view_rect(translation: Vec2, scale: f32, window: Vec2) -> Rect {
+    Rect::from_center_size(translation, window * scale)
+}
+
+/// Position of a point relative to the board, from 0 at the top left corner to 1 at the bottom right one.
+pub fn board_fraction(board: Rect, point: Vec2) -> Vec2 {
+    Vec2::new(point.x - board.min.x, board.max.y - point.y) / board.size()
+}
+
+/// Point of the world at the position relative to the board, the inverse of `board_fraction`.
+pub fn board_point(board: Rect, fraction: Vec2) -> Vec2 {
+    Vec2::new(board.min.x + fraction.x * board.width(), board.max.y - fraction.y * board.height())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_anchor_under_cursor() {
+        let anchor = Vec2::new(100.0, -50.0);
+        let (translation, scale) = zoom_at(Vec2::new(0.0, 0.0), 1.0, anchor, 2.0);
+        assert_eq!((translation, scale), (Vec2::new(-100.0, 50.0), 2.0));
+
+        let (_, scale) = zoom_at(Vec2::new(0.0, 0.0), 1.0, anchor, 1000.0);
+        assert_eq!(scale, MAX_ZOOM);
+        assert_eq!(zoom_at(Vec2::new(0.0, 0.0), MIN_ZOOM, anchor, 0.5), (Vec2::new(0.0, 0.0), MIN_ZOOM));
+    }
+
+    #[test]
+    fn board_is_fitted_and_kept_on_screen() {
+        let board = Rect::new(-20.0, -3980.0, 3980.0, 20.0);
+        let (center, scale) = fit(board, Vec2::new(1000.0, 800.0));
+        assert_eq!(center, Vec2::new(1980.0, -1980.0));
+        assert!((scale - 5.5).abs() < 0.001);
+        assert_eq!(fit(Rect::new(0.0, 0.0, 200.0, 100.0), Vec2::new(1000.0, 800.0)).1, 1.0);
+
+        assert_eq!(clamp_to_board(Vec2::new(5000.0, 0.0), board), Vec2::new(3980.0, 0.0));
+        assert_eq!(clamp_to_board(Vec2::new(100.0, -100.0), board), Vec2::new(100.0, -100.0));
+    }
+
+    #[test]
+    fn board_fractions_round_trip() {
+        let board = Rect::new(-20.0, -380.0, 780.0, 20.0);
+        let view = view_rect(Vec2::new(180.0, -80.0), 2.0, Vec2::new(100.0, 50.0));
+        assert_eq!(view, Rect::new(80.0, -130.0, 280.0, -30.0));
+
+        assert_eq!(board_fraction(board, view.min), Vec2::new(0.125, 0.375));
+        assert_eq!(board_fraction(board, Vec2::new(-20.0, 20.0)), Vec2::ZERO);
+        assert_eq!(board_point(board, Vec2::new(0.5, 0.25)), Vec2::new(380.0, -80.0));
+    }
+}
@@ -1,9 +1,31 @@
 
-use bevy::app::{App, AppExit};
+use bevy::app::App;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::app::AppExit;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::input::touchpad::TouchpadMagnify;
 use bevy::prelude::*;
 use bevy::prelude::MouseButton;
 use bevy::utils::HashMap;
 use crate::{GameState, LevelState};
+use crate::control::bindings::{KeyAction, KeyBindings};
+use crate::control::camera::{BoardBounds, FitBoard, clamp_to_board, fit, zoom_at};
+use crate::control::touch::{TouchAction, TouchGesture, pan_delta};
+
+pub mod bindings;
+pub mod camera;
+pub mod touch;
+
+/// Zoom of one line of the mouse wheel.
+const WHEEL_ZOOM: f32 = 1.1;
+/// Pixels of a touchpad scroll which zoom as much as one line of the wheel.
+const PIXELS_PER_LINE: f32 = 20.0;
+/// Pixels of the window a pressed button moves before a click becomes a drag.
+const DRAG_THRESHOLD: f32 = 2.0;
+/// Pixels of the window the camera passes in a second while a pan key is held.
+const PAN_SPEED: f32 = 600.0;
+/// Zoom of one press of a zoom key.
+const KEY_ZOOM: f32 = 1.25;
 
 pub struct ControlPlugin;
 
@@ -19,6 +41,25 @@ pub struct MouseButtonsState {
     pub right: bool,
     pub moved: HashMap<MouseButton, bool>,
     pub started_from: HashMap<MouseButton, Vec2>,
+    pub painting: HashMap<MouseButton, bool>,
+}
+
+/// Position of the cursor in the world, `None` when it is outside of the window.
+#[derive(Resource, Debug, Default)]
+pub struct CursorPosition(pub Option<Vec2>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DragPhase {
+    Start,
+    Move,
+    End,
+}
+
+/// Drag with the left button which paints instead of moving the camera.
+#[derive(Event)]
+pub struct DragEvent {
+    pub pos: Vec2,
+    pub phase: DragPhase,
 }
 
 #[derive(Event)]
@@ -35,48 +76,96 @@ impl Plugin for ControlPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<ClickEvent>()
+            .add_event::<DragEvent>()
+            .init_resource::<CursorPosition>()
+            .init_resource::<BoardBounds>()
+            .init_resource::<KeyBindings>()
             .add_event::<MoveCamera>()
+            .add_event::<FitBoard>()
             .add_event::<ExitGame>()
             .add_systems(Update, (
-                handle_click, set_camera_position
-            ).run_if(in_state(GameState::Level).and_then(in_state(LevelState::Process))))
+                handle_click,
+                handle_touches,
+                touch_cursor.after(handle_click),
+                set_camera_position,
+                zoom_camera,
+                fit_board,
+                clamp_camera.after(handle_click).after(handle_touches).after(set_camera_position).after(zoom_camera).after(fit_board),
+            ).run_if(in_state(GameState::Level).and_then(in_state(LevelState::Process))
+                .or_else(in_state(GameState::Editor))))
+            .add_systems(Update, keyboard_camera.before(clamp_camera)
+                .run_if(in_state(GameState::Level).and_then(in_state(LevelState::Process))))
+            .add_systems(Startup, load_key_bindings)
             .add_systems(Update, handle_exit)
         ;
     }
 }
 
+/// Middle and right buttons drag the camera, so does the left button while Space is held.
+/// Any other left drag paints and is reported by `DragEvent`, a press without moving is a `ClickEvent`.
 pub fn handle_click(
     events: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
     mut click_events: EventWriter<ClickEvent>,
+    mut drag_events: EventWriter<DragEvent>,
     windows: Query<&Window>,
-    mut camera_q: Query<(&Camera, &GlobalTransform, &mut Transform)>,
+    mut camera_q: Query<(&Camera, &GlobalTransform, &mut Transform, &OrthographicProjection)>,
+    mut cursor: ResMut<CursorPosition>,
     mut prev_state: Local<MouseButtonsState>,
 ) {
-    let (camera, camera_transform, mut transform) = camera_q.single_mut();
+    let (camera, camera_transform, mut transform, projection) = camera_q.single_mut();
+    let threshold = DRAG_THRESHOLD * projection.scale;
     let window = windows.single();
+    let pan = |button: &MouseButton| *button != MouseButton::Left || keys.pressed(KeyCode::Space);
 
-    if let Some(world_position) = window.cursor_position()
+    cursor.0 = window.cursor_position()
         .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
-        .map(|ray| ray.origin.truncate())
-    {
+        .map(|ray| ray.origin.truncate());
+
+    if let Some(world_position) = cursor.0 {
         for button in events.get_just_released() {
             if !prev_state.moved.get(button).unwrap_or(&false) {
                 click_events.send(ClickEvent {
                     pos: world_position,
                     button: button.clone()
                 });
+            } else if prev_state.painting.get(button).copied().unwrap_or(false) {
+                drag_events.send(DragEvent {
+                    pos: world_position,
+                    phase: DragPhase::End,
+                });
             }
+            prev_state.painting.insert(button.clone(), false);
         }
         for button in events.get_just_pressed() {
             prev_state.started_from.insert(button.clone(), world_position);
             prev_state.moved.insert(button.clone(), false);
+            prev_state.painting.insert(button.clone(), !pan(button));
         }
         for button in events.get_pressed() {
             let dt = prev_state.started_from.get(button)
                 .map(|from| *from - world_position)
                 .unwrap_or(Vec2::default());
 
-            if dt.length() > 2.0 {
+            if prev_state.painting.get(button).copied().unwrap_or(false) {
+                let moved = prev_state.moved.get(button).copied().unwrap_or(false);
+                if !moved && dt.length() > threshold {
+                    drag_events.send(DragEvent {
+                        pos: world_position + dt,
+                        phase: DragPhase::Start,
+                    });
+                    prev_state.moved.insert(button.clone(), true);
+                }
+                if prev_state.moved.get(button).copied().unwrap_or(false) {
+                    drag_events.send(DragEvent {
+                        pos: world_position,
+                        phase: DragPhase::Move,
+                    });
+                }
+                continue;
+            }
+
+            if dt.length() > threshold {
                 transform.translation += Vec3::from((dt, 0.));
                 prev_state.moved.insert(button.clone(), true);
             }
@@ -86,6 +175,76 @@ pub fn handle_click(
     }
 }
 
+/// A tap clicks like the left button, a long press like the right one and a sliding finger paints like a
+/// left drag. Two fingers pan the camera, their pinch is handled by `zoom_camera`.
+pub fn handle_touches(
+    touches: Res<Touches>,
+    time: Res<Time>,
+    interactions: Query<&Interaction>,
+    mut click_events: EventWriter<ClickEvent>,
+    mut drag_events: EventWriter<DragEvent>,
+    mut camera_q: Query<(&Camera, &GlobalTransform, &mut Transform, &OrthographicProjection)>,
+    mut gesture: Local<TouchGesture>,
+) {
+    let (camera, camera_transform, mut transform, projection) = camera_q.single_mut();
+    let fingers = touches.iter().count();
+    let over_ui = interactions.iter().any(|interaction| *interaction != Interaction::None);
+
+    let mut actions = vec![];
+    for touch in touches.iter_just_released() {
+        actions.extend(gesture.release(touch.id(), touch.position(), fingers));
+    }
+    for touch in touches.iter_just_canceled() {
+        actions.extend(gesture.release(touch.id(), touch.position(), fingers).into_iter()
+            .filter(|action| matches!(action, TouchAction::StrokeEnd(_))));
+    }
+    for touch in touches.iter_just_pressed() {
+        actions.extend(gesture.press(touch.id(), touch.position(), fingers, over_ui));
+    }
+    for touch in touches.iter() {
+        actions.extend(gesture.hold(touch.id(), touch.position(), time.delta_seconds()));
+    }
+
+    let world_position = |position: Vec2| camera.viewport_to_world(camera_transform, position)
+        .map(|ray| ray.origin.truncate());
+    let mut click = |position: Vec2, button: MouseButton| if let Some(pos) = world_position(position) {
+        click_events.send(ClickEvent { pos, button });
+    };
+    let mut drag = |position: Vec2, phase: DragPhase| if let Some(pos) = world_position(position) {
+        drag_events.send(DragEvent { pos, phase });
+    };
+    for action in actions {
+        match action {
+            TouchAction::Tap(position) => click(position, MouseButton::Left),
+            TouchAction::LongPress(position) => click(position, MouseButton::Right),
+            TouchAction::StrokeStart(position) => drag(position, DragPhase::Start),
+            TouchAction::StrokeMove(position) => drag(position, DragPhase::Move),
+            TouchAction::StrokeEnd(position) => drag(position, DragPhase::End),
+        }
+    }
+
+    if let [first, second] = touches.iter().collect::<Vec<_>>()[..] {
+        let previous = (first.previous_position() + second.previous_position()) / 2.0;
+        let current = (first.position() + second.position()) / 2.0;
+        transform.translation += pan_delta(previous, current, projection.scale).extend(0.);
+    }
+}
+
+/// A finger on the window moves the cursor, so pastes follow it like they follow the mouse.
+pub fn touch_cursor(
+    touches: Res<Touches>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut cursor: ResMut<CursorPosition>,
+) {
+    let Some(position) = touches.first_pressed_position() else {
+        return;
+    };
+    let (camera, camera_transform) = camera_q.single();
+    if let Some(ray) = camera.viewport_to_world(camera_transform, position) {
+        cursor.0 = Some(ray.origin.truncate());
+    }
+}
+
 pub fn set_camera_position(
     mut camera_q: Query<&mut Transform, With<Camera>>,
     mut events: EventReader<MoveCamera>,
@@ -101,6 +260,147 @@ pub fn set_camera_position(
     }
 }
 
+/// The mouse wheel and pinches of the touchpad or of two touches zoom around the cursor or the fingers.
+pub fn zoom_camera(
+    mut wheel_events: EventReader<MouseWheel>,
+    mut magnify_events: EventReader<TouchpadMagnify>,
+    touches: Res<Touches>,
+    windows: Query<&Window>,
+    mut camera_q: Query<(&Camera, &GlobalTransform, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let mut factor = 1.0;
+    for event in wheel_events.iter() {
+        factor *= match event.unit {
+            MouseScrollUnit::Line => WHEEL_ZOOM.powf(-event.y),
+            MouseScrollUnit::Pixel => WHEEL_ZOOM.powf(-event.y / PIXELS_PER_LINE),
+        };
+    }
+    for event in magnify_events.iter() {
+        factor /= 1.0 + event.0;
+    }
+
+    let mut anchor = windows.single().cursor_position();
+    if let [first, second] = touches.iter().collect::<Vec<_>>()[..] {
+        let before = first.previous_position().distance(second.previous_position());
+        let after = first.position().distance(second.position());
+        if before > 0.0 && after > 0.0 {
+            factor *= before / after;
+        }
+        anchor = Some((first.position() + second.position()) / 2.0);
+    }
+    if factor == 1.0 {
+        return;
+    }
+
+    let (camera, camera_transform, mut transform, mut projection) = camera_q.single_mut();
+    let anchor = anchor
+        .and_then(|anchor| camera.viewport_to_world(camera_transform, anchor))
+        .map(|ray| ray.origin.truncate())
+        .unwrap_or(transform.translation.truncate());
+    let (translation, scale) = zoom_at(transform.translation.truncate(), projection.scale, anchor, factor);
+    transform.translation = translation.extend(transform.translation.z);
+    projection.scale = scale;
+}
+
+pub fn fit_board(
+    mut events: EventReader<FitBoard>,
+    bounds: Res<BoardBounds>,
+    windows: Query<&Window>,
+    mut camera_q: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    events.clear();
+
+    if let Some(board) = bounds.0 {
+        let window = windows.single();
+        let (mut transform, mut projection) = camera_q.single_mut();
+        let (center, scale) = fit(board, Vec2::new(window.width(), window.height()));
+        transform.translation = center.extend(transform.translation.z);
+        projection.scale = scale;
+    }
+}
+
+/// Bound keys pan the camera while held, zoom around the center of the window and fit the board.
+pub fn keyboard_camera(
+    keys: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    time: Res<Time>,
+    mut fit_events: EventWriter<FitBoard>,
+    mut camera_q: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]) {
+        return;
+    }
+    let direction = [
+        (KeyAction::PanLeft, Vec2::NEG_X),
+        (KeyAction::PanRight, Vec2::X),
+        (KeyAction::PanUp, Vec2::Y),
+        (KeyAction::PanDown, Vec2::NEG_Y),
+    ].into_iter()
+        .filter(|(action, _)| bindings.pressed(&keys, *action))
+        .fold(Vec2::ZERO, |sum, (_, direction)| sum + direction);
+    let factor = match (bindings.just_pressed(&keys, KeyAction::ZoomIn), bindings.just_pressed(&keys, KeyAction::ZoomOut)) {
+        (true, false) => 1.0 / KEY_ZOOM,
+        (false, true) => KEY_ZOOM,
+        _others => 1.0,
+    };
+
+    let (mut transform, mut projection) = camera_q.single_mut();
+    if direction != Vec2::ZERO {
+        transform.translation += (direction * PAN_SPEED * projection.scale * time.delta_seconds()).extend(0.);
+    }
+    if factor != 1.0 {
+        let center = transform.translation.truncate();
+        projection.scale = zoom_at(center, projection.scale, center, factor).1;
+    }
+    if bindings.just_pressed(&keys, KeyAction::FitBoard) {
+        fit_events.send(FitBoard);
+    }
+}
+
+/// Moves the camera back when panning or zooming takes the board off screen.
+pub fn clamp_camera(
+    bounds: Res<BoardBounds>,
+    mut camera_q: Query<&mut Transform, With<Camera>>,
+) {
+    if let Some(board) = bounds.0 {
+        let mut transform = camera_q.single_mut();
+        let translation = transform.translation.truncate();
+        let clamped = clamp_to_board(translation, board);
+        if clamped != translation {
+            transform.translation = clamped.extend(transform.translation.z);
+        }
+    }
+}
+
+/// Reads keys remapped by the player, actions which aren't listed keep the default keys.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_key_bindings(
+    mut bindings: ResMut<KeyBindings>,
+) {
+    use std::fs;
+    use bevy::asset::FileAssetIo;
+    use crate::control::bindings::BINDINGS_FILE;
+
+    let path = FileAssetIo::get_base_path().join(BINDINGS_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+    match KeyBindings::from_ron(&content) {
+        Ok(loaded) => {
+            info!("Loaded key bindings from {:?}", path);
+            *bindings = loaded;
+        }
+        Err(error) => error!("Failed to load key bindings from {:?}: {}", path, error),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_key_bindings() {}
+
+#[cfg(not(target_arch = "wasm32"))]
 pub fn handle_exit(
     mut exit_game_events: EventReader<ExitGame>,
     mut exit_events: EventWriter<AppExit>,
@@ -108,4 +408,14 @@ pub fn handle_exit(
     for _ in exit_game_events.iter() {
         exit_events.send(AppExit::default())
     }
+}
+
+/// The browser game keeps running in the background, so the page can show it again without reloading.
+#[cfg(target_arch = "wasm32")]
+pub fn handle_exit(
+    mut exit_game_events: EventReader<ExitGame>,
+) {
+    for _ in exit_game_events.iter() {
+        crate::web::exit_to_page();
+    }
 }
\ No newline at end of file
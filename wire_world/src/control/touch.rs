@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+
+/// Pixels of the window a finger slides before a tap becomes a stroke.
+pub const TAP_DISTANCE: f32 = 10.0;
+/// Seconds a finger is held in place before it places an electron like the right button.
+pub const LONG_PRESS: f32 = 0.5;
+/// Pixels a finger slides across the level list to scroll it by a card.
+pub const SWIPE_DISTANCE: f32 = 50.0;
+
+/// What a finger does on the board, positions are in pixels of the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchAction {
+    Tap(Vec2),
+    LongPress(Vec2),
+    StrokeStart(Vec2),
+    StrokeMove(Vec2),
+    StrokeEnd(Vec2),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Finger {
+    id: u64,
+    start: Vec2,
+    last: Vec2,
+    held: f32,
+    stroke: bool,
+    long_pressed: bool,
+    /// The finger pressed a button, so it never touches the board.
+    over_ui: bool,
+}
+
+/// Turns a single finger into taps, long presses and strokes. A second finger starts a pan or a pinch,
+/// and the board isn't touched again until every finger is lifted.
+#[derive(Debug, Default)]
+pub struct TouchGesture {
+    finger: Option<Finger>,
+    multi: bool,
+}
+
+impl TouchGesture {
+    /// A finger touched the window, `fingers` counts every finger on the window including it.
+    pub fn press(&mut self, id: u64, position: Vec2, fingers: usize, over_ui: bool) -> Vec<TouchAction> {
+        if fingers > 1 || self.multi {
+            self.multi = true;
+            return self.finger.take()
+                .filter(|finger| finger.stroke)
+                .map(|finger| vec![TouchAction::StrokeEnd(finger.last)])
+                .unwrap_or_default();
+        }
+        self.finger = Some(Finger {
+            id,
+            start: position,
+            last: position,
+            held: 0.0,
+            stroke: false,
+            long_pressed: false,
+            over_ui,
+        });
+        vec![]
+    }
+
+    /// A finger stays on the window for `seconds` more.
+    pub fn hold(&mut self, id: u64, position: Vec2, seconds: f32) -> Vec<TouchAction> {
+        let Some(finger) = self.finger.as_mut().filter(|finger| finger.id == id && !finger.over_ui) else {
+            return vec![];
+        };
+        finger.held += seconds;
+        finger.last = position;
+
+        let mut actions = vec![];
+        if !finger.stroke && !finger.long_pressed && position.distance(finger.start) > TAP_DISTANCE {
+            finger.stroke = true;
+            actions.push(TouchAction::StrokeStart(finger.start));
+        }
+        if finger.stroke {
+            actions.push(TouchAction::StrokeMove(position));
+        } else if !finger.long_pressed && finger.held >= LONG_PRESS {
+            finger.long_pressed = true;
+            actions.push(TouchAction::LongPress(finger.start));
+        }
+        actions
+    }
+
+    /// A finger left the window, `fingers` counts the fingers which are still on it.
+    pub fn release(&mut self, id: u64, position: Vec2, fingers: usize) -> Vec<TouchAction> {
+        if fingers == 0 {
+            self.multi = false;
+        }
+        let Some(finger) = self.finger.filter(|finger| finger.id == id) else {
+            return vec![];
+        };
+        self.finger = None;
+
+        if finger.over_ui || finger.long_pressed {
+            vec![]
+        } else if finger.stroke {
+            vec![TouchAction::StrokeEnd(position)]
+        } else {
+            vec![TouchAction::Tap(finger.start)]
+        }
+    }
+}
+
+/// Camera movement which keeps the world under the middle of two fingers. `previous` and `current` are
+/// the middles in pixels of the window, where y grows downwards.
+pub fn pan_delta(previous: Vec2, current: Vec2, scale: f32) -> Vec2 {
+    let delta = (previous - current) * scale;
+    Vec2::new(delta.x, -delta.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finger_taps_long_presses_and_strokes() {
+        let mut gesture = TouchGesture::default();
+        let start = Vec2::new(100.0, 100.0);
+
+        assert_eq!(gesture.press(1, start, 1, false), vec![]);
+        assert_eq!(gesture.hold(1, Vec2::new(104.0, 100.0), 0.1), vec![]);
+        assert_eq!(gesture.release(1, Vec2::new(104.0, 100.0), 0), vec![TouchAction::Tap(start)]);
+
+        gesture.press(2, start, 1, false);
+        assert_eq!(gesture.hold(2, start, 0.3), vec![]);
+        assert_eq!(gesture.hold(2, start, 0.3), vec![TouchAction::LongPress(start)]);
+        assert_eq!(gesture.hold(2, Vec2::new(200.0, 100.0), 0.1), vec![]);
+        assert_eq!(gesture.release(2, start, 0), vec![]);
+
+        gesture.press(3, start, 1, false);
+        let end = Vec2::new(130.0, 100.0);
+        assert_eq!(gesture.hold(3, end, 0.1), vec![TouchAction::StrokeStart(start), TouchAction::StrokeMove(end)]);
+        assert_eq!(gesture.release(3, end, 0), vec![TouchAction::StrokeEnd(end)]);
+
+        gesture.press(4, start, 1, true);
+        assert_eq!(gesture.hold(4, end, 1.0), vec![]);
+        assert_eq!(gesture.release(4, end, 0), vec![]);
+    }
+
+    #[test]
+    fn second_finger_stops_the_board() {
+        let mut gesture = TouchGesture::default();
+        let start = Vec2::new(100.0, 100.0);
+        let moved = Vec2::new(150.0, 100.0);
+
+        gesture.press(1, start, 1, false);
+        gesture.hold(1, moved, 0.1);
+        assert_eq!(gesture.press(2, start, 2, false), vec![TouchAction::StrokeEnd(moved)]);
+        assert_eq!(gesture.hold(1, start, 1.0), vec![]);
+        assert_eq!(gesture.release(2, start, 1), vec![]);
+        assert_eq!(gesture.press(3, start, 2, false), vec![]);
+        assert_eq!(gesture.release(1, start, 1), vec![]);
+        assert_eq!(gesture.release(3, start, 0), vec![]);
+
+        gesture.press(4, start, 1, false);
+        assert_eq!(gesture.release(4, start, 0), vec![TouchAction::Tap(start)]);
+
+        assert_eq!(pan_delta(Vec2::new(100.0, 100.0), Vec2::new(110.0, 90.0), 2.0), Vec2::new(-20.0, -20.0));
+    }
+}
@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use crate::editor::resources::{EditorTool, Entry, EntryField, TextField};
+
+#[derive(Component, Event, Debug, PartialEq, Clone)]
+pub enum EditorActions {
+    Tool(EditorTool),
+    Resize(i32, i32),
+    Boundary,
+    ElectronAvailable,
+    Focus(TextField),
+    AddExercise,
+    RemoveExercise,
+    SelectExercise(i32),
+    Timeout(i32),
+    SelectEntry(Entry),
+    ChangeEntry(EntryField, i32),
+    Save,
+    Menu,
+}
+
+/// Root of the editor side panel, the panel is rebuilt when the edited level changes.
+#[derive(Component, Default)]
+pub struct EditorPanel;
+
+#[derive(Component, Default)]
+pub struct EditorUI;
+
+/// Sprite which shows a spawn or an output of the selected exercise on the board.
+#[derive(Component, Default)]
+pub struct EditorMarker;
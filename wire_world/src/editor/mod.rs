@@ -0,0 +1,33 @@
+use bevy::app::{App, Plugin};
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::editor::components::{EditorActions, EditorUI};
+use crate::editor::services::*;
+use crate::ui::level::delete_ui;
+
+pub mod components;
+pub mod resources;
+pub mod services;
+pub mod ui;
+
+/// Level editor: fixed cells, board size, edges, exercises with their spawns and outputs.
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EditorActions>()
+            .add_systems(OnEnter(GameState::Editor), (init_editor, ui::spawn_editor_ui))
+            .add_systems(OnExit(GameState::Editor), (destroy_editor, delete_ui::<EditorUI>))
+            .add_systems(Update, (
+                draw_editor_board,
+                handle_editor_clicks,
+                type_text,
+                editor_button_click,
+                ui::fill_editor_panel.after(editor_button_click).after(type_text),
+                ui::button_state.after(ui::button_system),
+                ui::button_system,
+            ).run_if(in_state(GameState::Editor)))
+        ;
+    }
+}
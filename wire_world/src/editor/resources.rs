@@ -0,0 +1,409 @@
+use bevy::prelude::*;
+
+use crate::world::components::{Boundary, CellType, Point};
+use crate::world::components::CellType::EMPTY;
+use crate::world::format::converted_path;
+use crate::world::packs::{level_file_name, rename_level};
+use crate::world::resources::{ExerciseData, World};
+
+pub const MAX_LEVEL_SIZE: usize = 1000;
+const NEW_LEVEL_SIZE: (usize, usize) = (19, 9);
+const NEW_EXERCISE_TIMEOUT: usize = 20;
+
+/// Level which is being edited together with the editor controls.
+#[derive(Resource, Debug, Clone)]
+pub struct EditorState {
+    /// Level file the level was opened from, `None` for a new level.
+    pub file_name: Option<String>,
+    pub level: World,
+    pub tool: EditorTool,
+    pub exercise: usize,
+    pub entry: Option<Entry>,
+    pub focus: Option<TextField>,
+    pub status: String,
+}
+
+/// What a click on the board does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorTool {
+    Cell(CellType),
+    Spawn,
+    Output(bool),
+}
+
+/// Selected spawn or output of the current exercise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entry {
+    Spawn(usize),
+    Output(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryField {
+    Instant,
+    From,
+    Until,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextField {
+    Title,
+    Description,
+}
+
+impl EditorState {
+    pub fn new(file_name: Option<String>, level: World) -> EditorState {
+        EditorState {
+            file_name,
+            level,
+            tool: EditorTool::Cell(CellType::WIRE(true)),
+            exercise: 0,
+            entry: None,
+            focus: None,
+            status: String::new(),
+        }
+    }
+
+    pub fn empty() -> EditorState {
+        let size = NEW_LEVEL_SIZE;
+        EditorState::new(None, World {
+            title: "New level".to_owned(),
+            size,
+            boundary: Boundary::default(),
+            map: vec![EMPTY(false); size.0 * size.1],
+            electron_available: false,
+            exercises: vec![],
+        })
+    }
+
+    pub fn current_exercise(&self) -> Option<&ExerciseData> {
+        self.level.exercises.get(self.exercise)
+    }
+
+    /// Applies the current tool to a cell and returns the new cell if the board was changed.
+    pub fn apply_tool(&mut self, point: &Point) -> Option<CellType> {
+        if point.0 >= self.level.size.0 || point.1 >= self.level.size.1 {
+            return None;
+        }
+
+        match self.tool.clone() {
+            EditorTool::Cell(cell_type) => {
+                let index = self.level.index(point);
+                self.level.map[index] = cell_type.clone();
+                Some(cell_type)
+            }
+            EditorTool::Spawn => {
+                self.toggle_spawn(point);
+                None
+            }
+            EditorTool::Output(expectation) => {
+                self.toggle_output(point, expectation);
+                None
+            }
+        }
+    }
+
+    /// Cells keep their places when the board grows or shrinks, new cells are empty.
+    /// Spawns and outputs left outside of a smaller board are removed.
+    pub fn resize(&mut self, width: i32, height: i32) {
+        let (old_width, old_height) = self.level.size;
+        let size = (
+            shift(old_width, width).clamp(1, MAX_LEVEL_SIZE),
+            shift(old_height, height).clamp(1, MAX_LEVEL_SIZE),
+        );
+        let mut map = vec![EMPTY(false); size.0 * size.1];
+        for y in 0..size.1.min(old_height) {
+            for x in 0..size.0.min(old_width) {
+                map[y * size.0 + x] = self.level.map[y * old_width + x].clone();
+            }
+        }
+
+        self.level.size = size;
+        self.level.map = map;
+
+        let inside = |point: &Point| point.0 < size.0 && point.1 < size.1;
+        for exercise in self.level.exercises.iter_mut() {
+            exercise.spawns.retain(|(point, _)| inside(point));
+            exercise.outputs.retain(|(point, ..)| inside(point));
+        }
+        self.entry = None;
+    }
+
+    pub fn add_exercise(&mut self) {
+        self.level.exercises.push(ExerciseData {
+            description: format!("Exercise {}", self.level.exercises.len() + 1),
+            timeout: NEW_EXERCISE_TIMEOUT,
+            spawns: vec![],
+            outputs: vec![],
+        });
+        self.exercise = self.level.exercises.len() - 1;
+        self.entry = None;
+    }
+
+    pub fn remove_exercise(&mut self) {
+        if self.exercise < self.level.exercises.len() {
+            self.level.exercises.remove(self.exercise);
+            self.exercise = self.exercise.min(self.level.exercises.len().saturating_sub(1));
+            self.entry = None;
+        }
+    }
+
+    pub fn select_exercise(&mut self, delta: i32) {
+        let last = self.level.exercises.len().saturating_sub(1);
+        self.exercise = shift(self.exercise, delta).min(last);
+        self.entry = None;
+    }
+
+    /// Output windows which end after a shorter timeout are cut at it.
+    pub fn change_timeout(&mut self, delta: i32) {
+        if let Some(exercise) = self.level.exercises.get_mut(self.exercise) {
+            exercise.timeout = shift(exercise.timeout, delta).max(1);
+            for (_point, _expectation, from, until) in exercise.outputs.iter_mut() {
+                *until = (*until).min(exercise.timeout);
+                *from = (*from).min(until.saturating_sub(1));
+            }
+        }
+    }
+
+    /// Changes a time of the selected spawn or output.
+    pub fn change_entry(&mut self, field: EntryField, delta: i32) {
+        let Some(exercise) = self.level.exercises.get_mut(self.exercise) else {
+            return;
+        };
+
+        match (self.entry, field) {
+            (Some(Entry::Spawn(index)), EntryField::Instant) => {
+                if let Some((_point, instant)) = exercise.spawns.get_mut(index) {
+                    *instant = shift(*instant, delta);
+                }
+            }
+            (Some(Entry::Output(index)), EntryField::From) => {
+                if let Some((_point, _expectation, from, until)) = exercise.outputs.get_mut(index) {
+                    *from = shift(*from, delta).min(until.saturating_sub(1));
+                }
+            }
+            (Some(Entry::Output(index)), EntryField::Until) => {
+                let timeout = exercise.timeout;
+                if let Some((_point, _expectation, from, until)) = exercise.outputs.get_mut(index) {
+                    *until = shift(*until, delta).min(timeout).max(*from + 1);
+                }
+            }
+            _others => {}
+        }
+    }
+
+    pub fn type_text(&mut self, symbol: char) {
+        if let Some(text) = self.focused_text() {
+            text.push(symbol);
+        }
+    }
+
+    pub fn erase_text(&mut self) {
+        if let Some(text) = self.focused_text() {
+            text.pop();
+        }
+    }
+
+    /// Name of the file in the assets directory the level is saved to.
+    /// Legacy levels are saved next to the original file in the RON format.
+    pub fn saved_file_name(&self) -> String {
+        match &self.file_name {
+            Some(file_name) if file_name.ends_with(".ron") => file_name.clone(),
+            Some(file_name) => converted_path(file_name.as_ref()).display().to_string(),
+            None => format!("{}.level.ron", file_stem(&self.level.title)),
+        }
+    }
+
+    fn focused_text(&mut self) -> Option<&mut String> {
+        match self.focus? {
+            TextField::Title => Some(&mut self.level.title),
+            TextField::Description => self.level.exercises.get_mut(self.exercise)
+                .map(|exercise| &mut exercise.description),
+        }
+    }
+
+    /// Adds a spawn at the cell or removes the one which is already there.
+    fn toggle_spawn(&mut self, point: &Point) {
+        let Some(exercise) = self.level.exercises.get_mut(self.exercise) else {
+            return;
+        };
+
+        if let Some(index) = exercise.spawns.iter().position(|(position, _)| position == point) {
+            exercise.spawns.remove(index);
+            self.entry = None;
+        } else {
+            exercise.spawns.push((point.clone(), 0));
+            self.entry = Some(Entry::Spawn(exercise.spawns.len() - 1));
+        }
+    }
+
+    /// Adds an output for the whole exercise or removes the one which is already at the cell.
+    fn toggle_output(&mut self, point: &Point, expectation: bool) {
+        let Some(exercise) = self.level.exercises.get_mut(self.exercise) else {
+            return;
+        };
+
+        if let Some(index) = exercise.outputs.iter().position(|(position, ..)| position == point) {
+            exercise.outputs.remove(index);
+            self.entry = None;
+        } else {
+            exercise.outputs.push((point.clone(), expectation, 0, exercise.timeout));
+            self.entry = Some(Entry::Output(exercise.outputs.len() - 1));
+        }
+    }
+}
+
+/// Adds a level to the manifest or replaces the file it was loaded from, also in the `after` lists of the
+/// levels and packs which require it.
+pub fn update_manifest(manifest: &str, old: Option<&str>, new: &str) -> String {
+    let mut lines: Vec<String> = manifest.lines().map(|line| line.to_owned()).collect();
+
+    if let Some(old) = old {
+        for line in lines.iter_mut() {
+            if let Some(renamed) = rename_level(line, old, new) {
+                *line = renamed;
+            }
+        }
+    }
+    if !lines.iter().any(|line| level_file_name(line) == Some(new)) {
+        lines.push(new.to_owned());
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn file_stem(title: &str) -> String {
+    let stem: String = title.trim()
+        .to_lowercase()
+        .chars()
+        .map(|symbol| if symbol.is_alphanumeric() { symbol } else { '_' })
+        .collect();
+
+    match stem.is_empty() {
+        true => "level".to_owned(),
+        false => stem,
+    }
+}
+
+fn shift(value: usize, delta: i32) -> usize {
+    if delta < 0 {
+        value.saturating_sub(delta.unsigned_abs() as usize)
+    } else {
+        value.saturating_add(delta as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::CellType::WIRE;
+
+    #[test]
+    fn resize_keeps_cells_in_place() {
+        let mut state = EditorState::empty();
+        state.resize(-16, -7);
+        state.apply_tool(&Point(1, 1));
+        state.resize(1, 0);
+
+        assert_eq!(state.level.size, (4, 2));
+        assert_eq!(state.level.map, vec![
+            EMPTY(false), EMPTY(false), EMPTY(false), EMPTY(false),
+            EMPTY(false), WIRE(true), EMPTY(false), EMPTY(false),
+        ]);
+    }
+
+    #[test]
+    fn shrinking_keeps_level_valid() {
+        let mut state = EditorState::empty();
+        state.add_exercise();
+        for point in [Point(2, 1), Point(15, 1), Point(3, 8)] {
+            state.tool = EditorTool::Cell(WIRE(false));
+            state.apply_tool(&point);
+            state.tool = EditorTool::Output(true);
+            state.apply_tool(&point);
+        }
+        state.tool = EditorTool::Spawn;
+        state.apply_tool(&Point(15, 1));
+
+        state.resize(-10, -1);
+        state.change_timeout(-15);
+
+        let exercise = state.current_exercise().unwrap();
+        assert_eq!(exercise.spawns, vec![]);
+        assert_eq!(exercise.outputs, vec![(Point(2, 1), true, 0, 5)]);
+        assert_eq!(state.entry, None);
+        assert!(state.level.validate().is_empty(), "{:?}", state.level.validate());
+    }
+
+    #[test]
+    fn toggle_spawns_and_outputs() {
+        let mut state = EditorState::empty();
+        state.tool = EditorTool::Spawn;
+        state.apply_tool(&Point(0, 0));
+        assert!(state.level.exercises.is_empty());
+
+        state.add_exercise();
+        state.apply_tool(&Point(0, 0));
+        state.change_entry(EntryField::Instant, 3);
+        state.tool = EditorTool::Output(true);
+        state.apply_tool(&Point(5, 0));
+        state.change_entry(EntryField::Until, -30);
+
+        let exercise = state.current_exercise().unwrap();
+        assert_eq!(exercise.spawns, vec![(Point(0, 0), 3)]);
+        assert_eq!(exercise.outputs, vec![(Point(5, 0), true, 0, 1)]);
+
+        state.apply_tool(&Point(5, 0));
+        assert!(state.current_exercise().unwrap().outputs.is_empty());
+        assert_eq!(state.entry, None);
+    }
+
+    #[test]
+    fn change_window_of_loaded_output_after_timeout() {
+        let level = World::from_string("3 1 dead\nfalse\nw w e\n1\nTitle\n\n10\n0\n1\ntrue 12 15 1 0\n".to_owned()).unwrap();
+        let mut state = EditorState::new(None, level);
+        state.entry = Some(Entry::Output(0));
+        state.change_entry(EntryField::Until, -1);
+        state.change_entry(EntryField::Until, 1);
+
+        assert_eq!(state.current_exercise().unwrap().outputs, vec![(Point(1, 0), true, 12, 13)]);
+        assert!(!state.level.validate().is_empty());
+    }
+
+    #[test]
+    fn type_into_focused_field() {
+        let mut state = EditorState::empty();
+        state.add_exercise();
+        state.focus = Some(TextField::Description);
+        state.erase_text();
+        state.type_text('!');
+
+        assert_eq!(state.current_exercise().unwrap().description, "Exercise !");
+    }
+
+    #[test]
+    fn choose_saved_file_name() {
+        let mut state = EditorState::empty();
+        state.level.title = "My Gate 2".to_owned();
+        assert_eq!(state.saved_file_name(), "my_gate_2.level.ron");
+
+        state.file_name = Some("diode.level".to_owned());
+        assert_eq!(state.saved_file_name(), "diode.level.ron");
+    }
+
+    #[test]
+    fn update_manifest_in_place() {
+        let manifest = "# levels\nfree.level\ndiode.level\n";
+        assert_eq!(
+            update_manifest(manifest, Some("diode.level"), "diode.level.ron"),
+            "# levels\nfree.level\ndiode.level.ron\n",
+        );
+        assert_eq!(update_manifest(manifest, None, "gate.level.ron"), "# levels\nfree.level\ndiode.level\ngate.level.ron\n");
+        assert_eq!(update_manifest(manifest, None, "free.level"), manifest);
+        assert_eq!(update_manifest("[Gates]\nor.level after diode.level\n", Some("or.level"), "or.level.ron"),
+                   "[Gates]\nor.level.ron after diode.level\n");
+        assert_eq!(update_manifest("[Gates]\ndiode.level\nblocker.level after diode.level\n[Logic] after Gates\n",
+                                   Some("diode.level"), "diode.level.ron"),
+                   "[Gates]\ndiode.level.ron\nblocker.level after diode.level.ron\n[Logic] after Gates\n");
+    }
+}
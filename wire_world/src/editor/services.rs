@@ -0,0 +1,266 @@
+use bevy::input::keyboard::KeyCode;
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+
+use crate::GameState;
+use crate::control::ClickEvent;
+use crate::control::camera::{BoardBounds, FitBoard};
+use crate::editor::components::{EditorActions, EditorMarker};
+use crate::editor::resources::{EditorState, TextField};
+use crate::world::CELL_SIZE;
+use crate::world::board::{BoardTexture, board_rect, cell_at, cell_center};
+use crate::world::components::Boundary;
+use crate::world::progress::Progress;
+use crate::world::resources::{LevelConfig, LevelsCatalog, World};
+use crate::world::simulation::Simulation;
+
+const MARKER_SIZE: f32 = CELL_SIZE / 2.;
+const SPAWN_COLOR: Color = Color::ORANGE;
+const OUTPUT_ON_COLOR: Color = Color::MIDNIGHT_BLUE;
+const OUTPUT_OFF_COLOR: Color = Color::CRIMSON;
+
+pub fn init_editor(
+    mut commands: Commands,
+    level_config: Res<LevelConfig>,
+    catalog: Res<LevelsCatalog>,
+    levels: Res<Assets<World>>,
+) {
+    let level = level_config.level_name.as_ref()
+        .and_then(|level_name| catalog.levels.iter().find(|(file_name, _)| file_name == level_name))
+        .and_then(|(file_name, handle)| Some((file_name.clone(), levels.get(handle)?.clone())));
+
+    match level {
+        Some((file_name, level)) => {
+            info!("Editing level {}", file_name);
+            commands.insert_resource(EditorState::new(Some(file_name), level));
+        }
+        None => {
+            info!("Editing new level");
+            commands.insert_resource(EditorState::empty());
+        }
+    }
+}
+
+pub fn destroy_editor(
+    mut commands: Commands,
+    entities: Query<Entity, With<Sprite>>,
+) {
+    commands.remove_resource::<EditorState>();
+    commands.remove_resource::<BoardTexture>();
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Redraws the whole board when its size or boundary is changed, and markers on every change.
+pub fn draw_editor_board(
+    mut commands: Commands,
+    state: Option<Res<EditorState>>,
+    board: Option<Res<BoardTexture>>,
+    sprites: Query<Entity, With<Sprite>>,
+    markers: Query<Entity, With<EditorMarker>>,
+    mut images: ResMut<Assets<Image>>,
+    mut bounds: ResMut<BoardBounds>,
+    mut fit_events: EventWriter<FitBoard>,
+    mut drawn: Local<Option<((usize, usize), Boundary)>>,
+) {
+    let Some(state) = state else {
+        return;
+    };
+    if !state.is_changed() {
+        return;
+    }
+
+    let layout = (state.level.size, state.level.boundary);
+    if board.is_none() || *drawn != Some(layout) {
+        for entity in sprites.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        let board = BoardTexture::spawn(&Simulation::new(&state.level), &mut commands, &mut images);
+        commands.insert_resource(board);
+
+        bounds.0 = Some(board_rect(state.level.size));
+        if drawn.map(|(size, _)| size) != Some(state.level.size) {
+            fit_events.send(FitBoard);
+        }
+        *drawn = Some(layout);
+    } else {
+        for entity in markers.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    if let Some(exercise) = state.current_exercise() {
+        let spawns = exercise.spawns.iter()
+            .map(|(point, _)| (point, SPAWN_COLOR));
+        let outputs = exercise.outputs.iter()
+            .map(|(point, expectation, ..)| (point, if *expectation { OUTPUT_ON_COLOR } else { OUTPUT_OFF_COLOR }));
+
+        for (point, color) in spawns.chain(outputs) {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(MARKER_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::from((cell_center(point), 1.))),
+                    ..default()
+                },
+                EditorMarker::default(),
+            ));
+        }
+    }
+}
+
+pub fn handle_editor_clicks(
+    mut click_events: EventReader<ClickEvent>,
+    state: Option<ResMut<EditorState>>,
+    board: Option<Res<BoardTexture>>,
+    mut images: ResMut<Assets<Image>>,
+    interactions: Query<&Interaction>,
+) {
+    if let (Some(mut state), Some(board)) = (state, board) {
+        for event in click_events.iter() {
+            if interactions.iter().any(|interaction| *interaction != Interaction::None) {
+                continue;
+            }
+            if event.button != MouseButton::Left {
+                continue;
+            }
+
+            if let Some(point) = cell_at(event.pos, state.level.size) {
+                state.focus = None;
+                if let Some(cell_type) = state.apply_tool(&point) {
+                    board.paint(&mut images, &point, cell_type.base_color());
+                }
+            }
+        }
+    }
+}
+
+/// Types into the focused title or description. Enter finishes the title, Escape finishes any field.
+pub fn type_text(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    state: Option<ResMut<EditorState>>,
+) {
+    let Some(mut state) = state else {
+        return;
+    };
+    if state.focus.is_none() {
+        characters.clear();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        state.focus = None;
+        characters.clear();
+        return;
+    }
+
+    for character in characters.iter() {
+        match character.char {
+            '\u{8}' | '\u{7f}' => state.erase_text(),
+            '\r' | '\n' => match state.focus {
+                Some(TextField::Description) => state.type_text('\n'),
+                _others => state.focus = None,
+            },
+            symbol if symbol.is_control() => {}
+            symbol => state.type_text(symbol),
+        }
+    }
+}
+
+pub fn editor_button_click(
+    mut actions: EventReader<EditorActions>,
+    state: Option<ResMut<EditorState>>,
+    asset_server: Res<AssetServer>,
+    mut progress: ResMut<Progress>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(mut state) = state else {
+        return;
+    };
+
+    for action in actions.iter() {
+        info!("{:?}", action);
+        if !matches!(action, EditorActions::Focus(_)) {
+            state.focus = None;
+        }
+
+        match action.clone() {
+            EditorActions::Tool(tool) => state.tool = tool,
+            EditorActions::Resize(width, height) => state.resize(width, height),
+            EditorActions::Boundary => state.level.boundary = state.level.boundary.next(),
+            EditorActions::ElectronAvailable => state.level.electron_available = !state.level.electron_available,
+            EditorActions::Focus(field) => {
+                state.focus = match state.focus == Some(field) {
+                    true => None,
+                    false => Some(field),
+                };
+            }
+            EditorActions::AddExercise => state.add_exercise(),
+            EditorActions::RemoveExercise => state.remove_exercise(),
+            EditorActions::SelectExercise(delta) => state.select_exercise(delta),
+            EditorActions::Timeout(delta) => state.change_timeout(delta),
+            EditorActions::SelectEntry(entry) => state.entry = Some(entry),
+            EditorActions::ChangeEntry(field, delta) => state.change_entry(field, delta),
+            EditorActions::Save => save(&mut state, &asset_server, &mut progress),
+            EditorActions::Menu => game_state.set(GameState::LevelsList),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save(state: &mut EditorState, asset_server: &AssetServer, progress: &mut Progress) {
+    let file_name = state.saved_file_name();
+
+    let problems = state.level.validate();
+    if let Some(problem) = problems.first() {
+        warn!("Level {} is not saved, it has {} problems", file_name, problems.len());
+        state.status = format!("Not saved, {} problems: {problem}", problems.len());
+        return;
+    }
+
+    match write_level(state, &file_name) {
+        Ok(()) => {
+            info!("Level is saved to {}", file_name);
+            asset_server.reload_asset(file_name.as_str());
+            asset_server.reload_asset("levels.manifest");
+
+            // A legacy level is saved under a new name, its solved flag, board and bests move with it.
+            if let Some(old) = state.file_name.as_deref().filter(|old| *old != file_name) {
+                progress.rename_level(old, &file_name);
+            }
+            state.status = format!("Saved to {file_name}");
+            state.file_name = Some(file_name);
+        }
+        Err(error) => {
+            error!("Failed to save level {}: {}", file_name, error);
+            state.status = format!("Failed to save {file_name}: {error}");
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save(state: &mut EditorState, _asset_server: &AssetServer, _progress: &mut Progress) {
+    state.status = "Levels can't be saved in the browser".to_owned();
+}
+
+/// Writes the level into the assets directory and puts it into the levels manifest.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_level(state: &EditorState, file_name: &str) -> anyhow::Result<()> {
+    use std::fs;
+    use bevy::asset::FileAssetIo;
+    use crate::editor::resources::update_manifest;
+
+    let assets = FileAssetIo::get_base_path().join("assets");
+    fs::write(assets.join(file_name), state.level.to_ron()?)?;
+
+    let manifest_path = assets.join("levels.manifest");
+    let manifest = fs::read_to_string(&manifest_path)?;
+    fs::write(&manifest_path, update_manifest(&manifest, state.file_name.as_deref(), file_name))?;
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+use bevy::text::BreakLineOn;
+
+use crate::editor::components::{EditorActions, EditorPanel, EditorUI};
+use crate::editor::resources::{EditorState, EditorTool, Entry, EntryField, TextField};
+use crate::ui::component::ButtonState;
+use crate::world::components::CellType::{ELECTRON, EMPTY, WIRE};
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+const SELECTED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+const FONT_SIZE: f32 = 20.0;
+
+pub fn spawn_editor_ui(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(0.0),
+                top: Val::Px(0.0),
+                width: Val::Px(420.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(8.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: Color::BLACK.with_a(0.7).into(),
+            ..default()
+        },
+        Interaction::default(),
+        EditorPanel::default(),
+        EditorUI::default(),
+    ));
+}
+
+/// Rebuilds the side panel when the edited level or the editor controls are changed.
+pub fn fill_editor_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Option<Res<EditorState>>,
+    spawned_panel: Query<Entity, Added<EditorPanel>>,
+    panel: Query<Entity, With<EditorPanel>>,
+) {
+    let Some(state) = state else {
+        return;
+    };
+    if !state.is_changed() && spawned_panel.is_empty() {
+        return;
+    }
+
+    if let Ok(panel) = panel.get_single() {
+        let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+        let style = TextStyle {
+            font,
+            font_size: FONT_SIZE,
+            color: Color::WHITE,
+        };
+
+        let mut panel = commands.entity(panel);
+        panel.despawn_descendants();
+        panel.with_children(|parent| {
+            let level = &state.level;
+
+            spawn_text_field(parent, &style, format!("Title: {}", level.title), EditorActions::Focus(TextField::Title));
+            spawn_row(parent, |parent| {
+                spawn_label(parent, &style, format!("Size {} x {}", level.size.0, level.size.1));
+                spawn_button(parent, &style, "W-", EditorActions::Resize(-1, 0));
+                spawn_button(parent, &style, "W+", EditorActions::Resize(1, 0));
+                spawn_button(parent, &style, "H-", EditorActions::Resize(0, -1));
+                spawn_button(parent, &style, "H+", EditorActions::Resize(0, 1));
+            });
+            spawn_row(parent, |parent| {
+                spawn_button(parent, &style, format!("Edges: {}", level.boundary), EditorActions::Boundary);
+                spawn_button(
+                    parent,
+                    &style,
+                    format!("Electrons: {}", if level.electron_available { "yes" } else { "no" }),
+                    EditorActions::ElectronAvailable,
+                );
+            });
+            spawn_label(parent, &style, "Cells, upper case are fixed:");
+            spawn_row(parent, |parent| {
+                for cell_type in [WIRE(true), EMPTY(true), ELECTRON(true), WIRE(false), EMPTY(false), ELECTRON(false)] {
+                    spawn_button(parent, &style, cell_type.symbol().to_string(), EditorActions::Tool(EditorTool::Cell(cell_type)));
+                }
+            });
+
+            spawn_row(parent, |parent| {
+                spawn_label(parent, &style, match level.exercises.len() {
+                    0 => "No exercises".to_owned(),
+                    count => format!("Exercise {} / {}", state.exercise + 1, count),
+                });
+                spawn_button(parent, &style, "<", EditorActions::SelectExercise(-1));
+                spawn_button(parent, &style, ">", EditorActions::SelectExercise(1));
+                spawn_button(parent, &style, "Add", EditorActions::AddExercise);
+                spawn_button(parent, &style, "Remove", EditorActions::RemoveExercise);
+            });
+
+            if let Some(exercise) = state.current_exercise() {
+                spawn_text_field(parent, &style, exercise.description.clone(), EditorActions::Focus(TextField::Description));
+                spawn_row(parent, |parent| {
+                    spawn_label(parent, &style, format!("Timeout {}", exercise.timeout));
+                    spawn_button(parent, &style, "-", EditorActions::Timeout(-1));
+                    spawn_button(parent, &style, "+", EditorActions::Timeout(1));
+                });
+                spawn_row(parent, |parent| {
+                    spawn_button(parent, &style, "Spawn", EditorActions::Tool(EditorTool::Spawn));
+                    spawn_button(parent, &style, "Output on", EditorActions::Tool(EditorTool::Output(true)));
+                    spawn_button(parent, &style, "Output off", EditorActions::Tool(EditorTool::Output(false)));
+                });
+
+                for (index, (point, instant)) in exercise.spawns.iter().enumerate() {
+                    spawn_button(
+                        parent,
+                        &style,
+                        format!("Spawn {} x {} at {}", point.0, point.1, instant),
+                        EditorActions::SelectEntry(Entry::Spawn(index)),
+                    );
+                }
+                for (index, (point, expectation, from, until)) in exercise.outputs.iter().enumerate() {
+                    spawn_button(
+                        parent,
+                        &style,
+                        format!(
+                            "Output {} x {} {} from {} until {}",
+                            point.0, point.1, if *expectation { "on" } else { "off" }, from, until,
+                        ),
+                        EditorActions::SelectEntry(Entry::Output(index)),
+                    );
+                }
+
+                match state.entry {
+                    Some(Entry::Spawn(_)) => spawn_row(parent, |parent| {
+                        spawn_label(parent, &style, "Instant");
+                        spawn_button(parent, &style, "-", EditorActions::ChangeEntry(EntryField::Instant, -1));
+                        spawn_button(parent, &style, "+", EditorActions::ChangeEntry(EntryField::Instant, 1));
+                    }),
+                    Some(Entry::Output(_)) => spawn_row(parent, |parent| {
+                        spawn_label(parent, &style, "From");
+                        spawn_button(parent, &style, "-", EditorActions::ChangeEntry(EntryField::From, -1));
+                        spawn_button(parent, &style, "+", EditorActions::ChangeEntry(EntryField::From, 1));
+                        spawn_label(parent, &style, "Until");
+                        spawn_button(parent, &style, "-", EditorActions::ChangeEntry(EntryField::Until, -1));
+                        spawn_button(parent, &style, "+", EditorActions::ChangeEntry(EntryField::Until, 1));
+                    }),
+                    None => {}
+                }
+            }
+
+            spawn_row(parent, |parent| {
+                spawn_button(parent, &style, "Save", EditorActions::Save);
+                spawn_button(parent, &style, "Menu", EditorActions::Menu);
+            });
+            if !state.status.is_empty() {
+                spawn_label(parent, &style, state.status.clone());
+            }
+        });
+    }
+}
+
+pub fn button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut ButtonState, &EditorActions),
+        (Changed<Interaction>, With<Button>),
+    >,
+    touches: Res<Touches>,
+    mut actions: EventWriter<EditorActions>,
+) {
+    for (interaction, mut state, action) in interaction_query.iter_mut() {
+        if state.clicked(*interaction, &touches) {
+            actions.send(action.clone());
+        }
+        state.prev_interaction = interaction.clone();
+    }
+}
+
+pub fn button_state(
+    mut buttons: Query<(&mut BackgroundColor, &ButtonState, &EditorActions), With<Button>>,
+    state: Option<Res<EditorState>>,
+) {
+    let Some(state) = state else {
+        return;
+    };
+
+    for (mut color, button_state, action) in buttons.iter_mut() {
+        let selected = match action {
+            EditorActions::Tool(tool) => *tool == state.tool,
+            EditorActions::Focus(field) => Some(*field) == state.focus,
+            EditorActions::SelectEntry(entry) => Some(*entry) == state.entry,
+            _others => false,
+        };
+
+        *color = match button_state.prev_interaction {
+            Interaction::Hovered => HOVERED_BUTTON,
+            Interaction::Pressed => PRESSED_BUTTON,
+            Interaction::None if selected => SELECTED_BUTTON,
+            Interaction::None => NORMAL_BUTTON,
+        }.into();
+    }
+}
+
+fn spawn_row(parent: &mut ChildBuilder, children: impl FnOnce(&mut ChildBuilder)) {
+    parent.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(6.0),
+            ..default()
+        },
+        ..default()
+    }).with_children(children);
+}
+
+fn spawn_label(parent: &mut ChildBuilder, style: &TextStyle, text: impl Into<String>) {
+    parent.spawn(TextBundle {
+        text: Text {
+            sections: vec![TextSection::new(text, style.clone())],
+            alignment: TextAlignment::Left,
+            linebreak_behavior: BreakLineOn::WordBoundary,
+        },
+        ..default()
+    });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, style: &TextStyle, text: impl Into<String>, action: EditorActions) {
+    parent.spawn((
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: NORMAL_BUTTON.into(),
+            ..default()
+        },
+        ButtonState::default(),
+        action,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(text, style.clone()));
+    });
+}
+
+/// Button which shows an editable text, typing goes into the text while the button is selected.
+fn spawn_text_field(parent: &mut ChildBuilder, style: &TextStyle, text: String, action: EditorActions) {
+    parent.spawn((
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(8.0)),
+                min_height: Val::Px(36.0),
+                ..default()
+            },
+            background_color: NORMAL_BUTTON.into(),
+            ..default()
+        },
+        ButtonState::default(),
+        action,
+    )).with_children(|parent| {
+        spawn_label(parent, style, text);
+    });
+}
@@ -1,6 +1,10 @@
 pub mod world;
 pub mod control;
 pub mod ui;
+pub mod cli;
+pub mod editor;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
 
 
 use bevy::app::App;
@@ -10,8 +14,10 @@ use bevy::prelude::*;
 
 use bevy_tweening::*;
 use crate::control::ControlPlugin;
+use crate::editor::EditorPlugin;
 use crate::ui::UiPlugin;
 use crate::world::components::Point;
+use crate::world::resources::World;
 
 
 use crate::world::WorldPlugin;
@@ -21,6 +27,7 @@ pub enum GameState {
     #[default]
     LevelsList,
     Level,
+    Editor,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default, States)]
@@ -30,64 +37,25 @@ pub enum LevelState {
     Finish,
 }
 
-const LEVELS: [LevelDescription<'static>; 8] = [
-    LevelDescription {
-        file_name: "free.level",
-        size: Point(100, 100),
-        title: "Free",
-        exercise_count: 0,
-    },
-    LevelDescription {
-        file_name: "crossroad.level",
-        size: Point(19, 9),
-        title: "Crossroad",
-        exercise_count: 4,
-    },
-    LevelDescription {
-        file_name: "forward_blocker.level",
-        size: Point(19, 9),
-        title: "Forward blocker",
-        exercise_count: 2,
-    },
-    LevelDescription {
-        file_name: "diode.level",
-        size: Point(19, 9),
-        title: "Diode",
-        exercise_count: 2,
-    },
-    LevelDescription {
-        file_name: "blocker.level",
-        size: Point(19, 9),
-        title: "Blocker",
-        exercise_count: 2,
-    },
-    LevelDescription {
-        file_name: "or.level",
-        size: Point(19, 9),
-        title: "Logic OR",
-        exercise_count: 4,
-    },
-    LevelDescription {
-        file_name: "xor.level",
-        size: Point(19, 9),
-        title: "Logic XOR",
-        exercise_count: 4,
-    },
-    LevelDescription {
-        file_name: "and.level",
-        size: Point(19, 9),
-        title: "Logic AND",
-        exercise_count: 4,
-    },
-];
-
-pub struct LevelDescription<'a> {
-    pub file_name: &'a str,
+/// Card of a level in the level menu, built from a loaded level file.
+pub struct LevelDescription {
+    pub file_name: String,
     pub size: Point,
-    pub title: &'a str,
+    pub title: String,
     pub exercise_count: usize,
 }
 
+impl LevelDescription {
+    pub fn new(file_name: &str, world: &World) -> LevelDescription {
+        LevelDescription {
+            file_name: file_name.to_owned(),
+            size: Point(world.size.0, world.size.1),
+            title: world.title.clone(),
+            exercise_count: world.exercises.len(),
+        }
+    }
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -107,12 +75,16 @@ impl Plugin for GamePlugin {
             .add_plugins(WorldPlugin)
             .add_plugins(ControlPlugin)
             .add_plugins(UiPlugin)
+            .add_plugins(EditorPlugin)
             .add_systems(Startup, init)
             .add_systems(
                 Update,
                 component_animator_system::<Camera2d>.in_set(AnimationSystem::AnimationUpdate),
             )
         ;
+
+        #[cfg(target_arch = "wasm32")]
+        app.add_plugins(web::WebPlugin);
     }
 }
 
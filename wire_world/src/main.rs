@@ -1,8 +1,14 @@
 use bevy::prelude::*;
 use zhdanov_wire_world::GamePlugin;
+use zhdanov_wire_world::cli;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     App::new()
         .add_plugins(GamePlugin)
         .run();
-}
\ No newline at end of file
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+
+use crate::ui::component::{AnalyzerPanel, LevelActions, LevelUI, WaveColumn};
+use crate::world::components::CellType::{ELECTRON, TAIL, WIRE};
+use crate::world::probes::{Probes, Signal, TRACE_LENGTH};
+
+const FONT_SIZE: f32 = 18.0;
+const COLUMN_WIDTH: f32 = 4.0;
+const WAVE_HEIGHT: f32 = 24.0;
+
+pub fn spawn_analyzer_panel(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                top: Val::Px(70.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            background_color: Color::BLACK.with_a(0.7).into(),
+            ..default()
+        },
+        Interaction::default(),
+        AnalyzerPanel::default(),
+        LevelUI::default(),
+    ));
+}
+
+/// Lays out a row per probe when probes are attached or removed.
+pub fn fill_analyzer_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    probes: Res<Probes>,
+    spawned_panel: Query<Entity, Added<AnalyzerPanel>>,
+    mut panel: Query<(Entity, &mut Style), With<AnalyzerPanel>>,
+    mut revision: Local<Option<usize>>,
+) {
+    let Ok((panel, mut style)) = panel.get_single_mut() else {
+        return;
+    };
+    let display = match probes.panel && !probes.probes.is_empty() {
+        true => Display::Flex,
+        false => Display::None,
+    };
+    if style.display != display {
+        style.display = display;
+    }
+    if *revision == Some(probes.revision) && spawned_panel.is_empty() {
+        return;
+    }
+    *revision = Some(probes.revision);
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+    };
+    let mut panel = commands.entity(panel);
+    panel.despawn_descendants();
+    panel.with_children(|parent| {
+        for (index, probe) in probes.probes.iter().enumerate() {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            }).with_children(|parent| {
+                parent.spawn(TextBundle {
+                    style: Style {
+                        width: Val::Px(50.0),
+                        ..default()
+                    },
+                    ..TextBundle::from_section(probe.name.clone(), text_style.clone())
+                });
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(COLUMN_WIDTH * TRACE_LENGTH as f32),
+                        height: Val::Px(WAVE_HEIGHT),
+                        align_items: AlignItems::FlexEnd,
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.05, 0.05, 0.05).into(),
+                    ..default()
+                }).with_children(|parent| {
+                    for column in 0..TRACE_LENGTH {
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(COLUMN_WIDTH),
+                                    height: Val::Percent(0.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            WaveColumn { probe: index, column },
+                        ));
+                    }
+                });
+            });
+        }
+    });
+}
+
+/// Draws traces like a logic analyzer: the newest sample is on the right, an electron is
+/// a high level, a tail is a falling edge and a wire is the low level.
+pub fn update_waveforms(
+    probes: Res<Probes>,
+    mut columns: Query<(&WaveColumn, &mut Style, &mut BackgroundColor)>,
+    spawned_columns: Query<(), Added<WaveColumn>>,
+) {
+    if !probes.is_changed() && spawned_columns.is_empty() {
+        return;
+    }
+
+    for (wave_column, mut style, mut color) in columns.iter_mut() {
+        let Some(probe) = probes.probes.get(wave_column.probe) else {
+            continue;
+        };
+        let offset = TRACE_LENGTH - probe.trace.len();
+        let signal = wave_column.column.checked_sub(offset)
+            .and_then(|index| probe.trace.get(index));
+
+        let (height, cell_type) = match signal {
+            Some(Signal::Electron) => (100.0, Some(ELECTRON(false))),
+            Some(Signal::Tail) => (60.0, Some(TAIL(false))),
+            Some(Signal::Wire) => (15.0, Some(WIRE(false))),
+            Some(Signal::Empty) | None => (0.0, None),
+        };
+        style.height = Val::Percent(height);
+        *color = cell_type.map(|cell_type| cell_type.base_color())
+            .unwrap_or(Color::NONE)
+            .into();
+    }
+}
+
+pub fn analyzer_click(
+    mut actions: EventReader<LevelActions>,
+    mut probes: ResMut<Probes>,
+) {
+    for action in actions.iter() {
+        if *action == LevelActions::Analyzer {
+            probes.panel = !probes.panel;
+        }
+    }
+}
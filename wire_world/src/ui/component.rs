@@ -1,16 +1,49 @@
 use bevy::prelude::*;
 
+use crate::control::touch::TAP_DISTANCE;
+use crate::world::tools::Tool;
+
 #[derive(Component, Event, Debug, PartialEq, Clone)]
 pub enum LevelActions {
     Menu,
     Pause,
     Play(f32),
     Restart,
+    Undo,
+    Redo,
+    Tool(Tool),
+    Copy,
+    Cut,
+    Paste,
+    Rotate,
+    MirrorHorizontal,
+    MirrorVertical,
+    /// Shows or hides the pattern library.
+    Patterns,
+    /// Starts pasting the pattern with the given index in the library.
+    Stamp(usize),
+    SavePattern,
+    /// Advances the board by one generation and pauses.
+    Step,
+    /// Returns the board to the previous generation and pauses.
+    StepBack,
+    /// Focuses or leaves the run to tick field.
+    RunTo,
+    /// Shows or hides the waveforms of probes.
+    Analyzer,
+    /// Hides the explanation of the last failure.
+    Dismiss,
+    /// Opens the given level from the finish screen.
+    NextLevel(String),
+    /// Centers the board and zooms out until it fits the window.
+    FitBoard,
 }
 
 #[derive(Component, Event, Debug, PartialEq, Clone)]
 pub enum MenuActions {
     Level(String),
+    /// Opens a level in the editor, `None` starts a new level.
+    Edit(Option<String>),
     Scroll(i32),
     Close,
 }
@@ -20,14 +53,81 @@ pub struct ButtonState {
     pub prev_interaction: Interaction,
 }
 
+impl ButtonState {
+    /// A pressed button is clicked when the mouse is released over it. A lifted finger leaves no cursor, so
+    /// the button is not hovered after a tap; it is clicked unless the finger slid away to scroll.
+    pub fn clicked(&self, interaction: Interaction, touches: &Touches) -> bool {
+        self.prev_interaction == Interaction::Pressed && match interaction {
+            Interaction::Hovered => true,
+            Interaction::None => touches.iter_just_released()
+                .any(|touch| touch.distance().length() <= TAP_DISTANCE),
+            Interaction::Pressed => false,
+        }
+    }
+}
+
 #[derive(Component, Default)]
 pub struct LevelUI;
 
 #[derive(Component, Default)]
 pub struct LevelFinishUI;
 
+/// Text of the run to tick button, it shows the current tick.
+#[derive(Component, Default)]
+pub struct TickLabel;
+
+#[derive(Component, Default)]
+pub struct LevelErrorUI;
+
+/// Side panel with the pattern library, it is hidden until the library button is pressed.
+#[derive(Component, Default)]
+pub struct PatternPanel;
+
+/// Explanation of the last failed exercise.
+#[derive(Component, Default)]
+pub struct FailureUI;
+
+/// Side panel with waveforms of probes.
+#[derive(Component, Default)]
+pub struct AnalyzerPanel;
+
+/// Column of a waveform, it shows one sample of a probe trace.
+#[derive(Component)]
+pub struct WaveColumn {
+    pub probe: usize,
+    pub column: usize,
+}
+
+/// Bar under the board which scrubs the recorded exercise run.
+#[derive(Component, Default)]
+pub struct Timeline;
+
+/// Part of the timeline covered by the recording.
+#[derive(Component, Default)]
+pub struct TimelineFill;
+
+/// Tick shown on the board.
+#[derive(Component, Default)]
+pub struct TimelineHead;
+
+/// Spawn instants and output windows of the recorded exercise.
+#[derive(Component, Default)]
+pub struct TimelineMarker;
+
+/// Whole board at one pixel per cell, pressing on it moves the camera there.
+#[derive(Component, Default)]
+pub struct Minimap;
+
+/// Part of the board shown by the camera.
+#[derive(Component, Default)]
+pub struct MinimapView;
+
 #[derive(Component, Default)]
 pub struct LevelMenuUI;
 
+/// Card of a level in the level menu by its position in the list.
+#[derive(Component)]
+pub struct LevelCard(pub usize);
+
 #[derive(Component, Default)]
 pub struct LevelsListNode;
\ No newline at end of file
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy::text::BreakLineOn;
+
+use crate::ui::component::{FailureUI, LevelActions, LevelUI};
+use crate::ui::patterns::spawn_text_button;
+use crate::world::failure::Failure;
+
+const FONT_SIZE: f32 = 22.0;
+
+/// Shows the explanation when an exercise fails, the previous one is replaced.
+pub fn spawn_failure_message(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    failure: Option<Res<Failure>>,
+    messages: Query<Entity, With<FailureUI>>,
+) {
+    let Some(failure) = failure.filter(|failure| failure.is_changed()) else {
+        return;
+    };
+    for entity in messages.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+    };
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(75.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        FailureUI::default(),
+        LevelUI::default(),
+    )).with_children(|parent| {
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    max_width: Val::Px(640.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    column_gap: Val::Px(12.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::MAROON.into(),
+                ..default()
+            },
+            Interaction::default(),
+        )).with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text {
+                    sections: vec![TextSection::new(failure.message(), style.clone())],
+                    alignment: TextAlignment::Left,
+                    linebreak_behavior: BreakLineOn::WordBoundary,
+                },
+                style: Style {
+                    max_width: Val::Px(500.0),
+                    ..default()
+                },
+                ..default()
+            });
+            spawn_text_button(parent, &style, "Dismiss", LevelActions::Dismiss);
+        });
+    });
+}
+
+/// The dismiss button and Escape hide the explanation and the highlighted cell.
+pub fn dismiss_failure(
+    mut commands: Commands,
+    mut actions: EventReader<LevelActions>,
+    keys: Res<Input<KeyCode>>,
+    failure: Option<Res<Failure>>,
+    messages: Query<Entity, With<FailureUI>>,
+) {
+    let dismissed = actions.iter().any(|action| *action == LevelActions::Dismiss)
+        || keys.just_pressed(KeyCode::Escape);
+    if failure.is_none() || !dismissed {
+        return;
+    }
+
+    commands.remove_resource::<Failure>();
+    for entity in messages.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
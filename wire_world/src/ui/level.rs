@@ -1,35 +1,43 @@
 
 use std::time::Duration;
 
+use bevy::asset::LoadState;
 use bevy::prelude::*;
 use bevy::text::BreakLineOn;
 
 use crate::{GameState, LevelState};
-use crate::ui::component::{ButtonState, LevelActions, LevelFinishUI, LevelUI};
-use crate::world::components::ChangeExercise;
-use crate::world::resources::{Counter, WorldState};
+use crate::control::bindings::{KeyAction, KeyBindings};
+use crate::control::camera::FitBoard;
+use crate::ui::component::{ButtonState, LevelActions, LevelErrorUI, LevelFinishUI, LevelUI, TickLabel};
+use crate::world::clipboard::{Pattern, clear};
+use crate::world::components::{Change, ChangeExercise, Edit, Exercise, OpenLevel};
+use crate::world::failure::Failure;
+use crate::world::history::EditHistory;
+use crate::world::progress::Progress;
+use crate::world::resources::{Counter, LevelConfig, LevelLoadErrors, LevelsCatalog, WorldState};
+use crate::world::recording::Recording;
+use crate::world::score::Scoring;
+use crate::world::stepper::Stepper;
+use crate::world::tools::{Tool, Toolbox};
 
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 const SELECTED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+const DISABLED_BUTTON: Color = Color::rgb(0.1, 0.1, 0.1);
 
 pub fn button_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &mut ButtonState, &LevelActions),
         (Changed<Interaction>, With<Button>),
     >,
+    touches: Res<Touches>,
     mut actions: EventWriter<LevelActions>,
 ) {
     for (interaction, _color, mut state, action)
     in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Hovered => {
-                if state.prev_interaction == Interaction::Pressed {
-                    actions.send(action.clone());
-                }
-            }
-            _other => {}
+        if state.clicked(*interaction, &touches) {
+            actions.send(action.clone());
         }
         state.prev_interaction = interaction.clone();
     }
@@ -38,8 +46,30 @@ pub fn button_system(
 pub fn button_state(
     mut buttons: Query<(&mut BackgroundColor, &mut ButtonState, &LevelActions), With<Button>>,
     counter: Res<Counter>,
+    world: Option<Res<WorldState>>,
+    history: Res<EditHistory>,
+    toolbox: Res<Toolbox>,
+    stepper: Res<Stepper>,
 ) {
+    let locked = world.map(|world| world.lock).unwrap_or(true);
+
     for (mut color, button_state, action) in buttons.iter_mut() {
+        let disabled = match action {
+            LevelActions::Undo => locked || !history.can_undo(),
+            LevelActions::Redo => locked || !history.can_redo(),
+            LevelActions::Copy => toolbox.selection.is_none(),
+            LevelActions::Cut => locked || toolbox.selection.is_none(),
+            LevelActions::SavePattern => toolbox.selection.is_none(),
+            LevelActions::Paste | LevelActions::Rotate | LevelActions::MirrorHorizontal
+            | LevelActions::MirrorVertical => toolbox.clipboard.is_none(),
+            LevelActions::StepBack => !stepper.can_step_back(),
+            _others => false,
+        };
+        if disabled {
+            *color = DISABLED_BUTTON.into();
+            continue;
+        }
+
         match button_state.prev_interaction {
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -50,7 +80,10 @@ pub fn button_state(
             Interaction::None => {
                 *color = NORMAL_BUTTON.into();
 
-                if *action == LevelActions::Pause && counter.timer.paused() {
+                if *action == LevelActions::Tool(toolbox.tool) && !toolbox.pasting
+                    || *action == LevelActions::Paste && toolbox.pasting
+                    || *action == LevelActions::RunTo && stepper.input.is_some()
+                    || *action == LevelActions::Pause && counter.timer.paused() {
                     *color = SELECTED_BUTTON.into();
                 } else if let LevelActions::Play(speed) = action {
                     if !counter.timer.paused() && *speed == counter.timer.duration().as_secs_f32() {
@@ -67,10 +100,15 @@ pub fn setup(
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
     let buttons_handle = asset_server.load("ui/buttons.png");
     let texture_atlas =
         TextureAtlas::from_grid(buttons_handle, Vec2::new(16.0, 16.0),
-                                6, 2, None, None);
+                                6, 6, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
     commands
         .spawn((
@@ -99,6 +137,8 @@ pub fn setup(
                 }
             ).with_children(|parent| {
                 spawn_button(parent, texture_atlas_handle.clone(), 0, LevelActions::Menu);
+                spawn_button(parent, texture_atlas_handle.clone(), 12, LevelActions::Undo);
+                spawn_button(parent, texture_atlas_handle.clone(), 13, LevelActions::Redo);
             });
             parent.spawn(
                 NodeBundle {
@@ -116,26 +156,92 @@ pub fn setup(
                 spawn_button(parent, texture_atlas_handle.clone(), 2, LevelActions::Pause);
                 spawn_button(parent, texture_atlas_handle.clone(), 3, LevelActions::Play(0.5));
                 spawn_button(parent, texture_atlas_handle.clone(), 4, LevelActions::Play(0.125));
+                spawn_button(parent, texture_atlas_handle.clone(), 28, LevelActions::StepBack);
+                spawn_button(parent, texture_atlas_handle.clone(), 27, LevelActions::Step);
+                parent.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            min_width: Val::Px(130.0),
+                            height: Val::Px(65.0),
+                            padding: UiRect::horizontal(Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    ButtonState::default(),
+                    LevelActions::RunTo,
+                )).with_children(|parent| {
+                    parent.spawn((TextBundle::from_section("Tick 0", text_style.clone()), TickLabel::default()));
+                });
             });
         });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            LevelUI::default(),
+        ))
+        .with_children(|parent| {
+            spawn_button(parent, texture_atlas_handle.clone(), 14, LevelActions::Tool(Tool::Single));
+            spawn_button(parent, texture_atlas_handle.clone(), 15, LevelActions::Tool(Tool::Brush));
+            spawn_button(parent, texture_atlas_handle.clone(), 16, LevelActions::Tool(Tool::Line));
+            spawn_button(parent, texture_atlas_handle.clone(), 17, LevelActions::Tool(Tool::Rect));
+            spawn_button(parent, texture_atlas_handle.clone(), 18, LevelActions::Tool(Tool::Eraser));
+            spawn_button(parent, texture_atlas_handle.clone(), 19, LevelActions::Tool(Tool::Select));
+            spawn_button(parent, texture_atlas_handle.clone(), 8, LevelActions::Tool(Tool::Probe));
+            spawn_button(parent, texture_atlas_handle.clone(), 20, LevelActions::Copy);
+            spawn_button(parent, texture_atlas_handle.clone(), 21, LevelActions::Cut);
+            spawn_button(parent, texture_atlas_handle.clone(), 22, LevelActions::Paste);
+            spawn_button(parent, texture_atlas_handle.clone(), 23, LevelActions::Rotate);
+            spawn_button(parent, texture_atlas_handle.clone(), 24, LevelActions::MirrorHorizontal);
+            spawn_button(parent, texture_atlas_handle.clone(), 25, LevelActions::MirrorVertical);
+            spawn_button(parent, texture_atlas_handle.clone(), 26, LevelActions::Patterns);
+            spawn_button(parent, texture_atlas_handle.clone(), 29, LevelActions::Analyzer);
+            spawn_button(parent, texture_atlas_handle.clone(), 33, LevelActions::FitBoard);
+        });
 }
 
 pub fn setup_finish_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    scoring: Res<Scoring>,
+    level_config: Res<LevelConfig>,
+    catalog: Res<LevelsCatalog>,
+    progress: Res<Progress>,
 ) {
+    let next_level = level_config.level_name.as_ref()
+        .and_then(|level_name| catalog.packs.next_level(level_name, &progress))
+        .map(|next_level| next_level.to_owned());
     let buttons_handle = asset_server.load("ui/buttons.png");
     let texture_atlas =
         TextureAtlas::from_grid(buttons_handle, Vec2::new(16.0, 16.0),
-                                6, 2, None, None);
+                                6, 6, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
     let text_style = TextStyle {
-        font,
+        font: font.clone(),
         font_size: 32.0,
         color: Color::WHITE,
     };
+    let score_style = TextStyle {
+        font,
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
 
     commands
         .spawn((
@@ -156,7 +262,7 @@ pub fn setup_finish_screen(
                 NodeBundle {
                     style: Style {
                         width: Val::Px(400.0),
-                        height: Val::Px(260.0),
+                        height: Val::Px(360.0),
                         align_items: AlignItems::Center,
                         justify_content: JustifyContent::Center,
                         flex_direction: FlexDirection::Column,
@@ -177,6 +283,16 @@ pub fn setup_finish_screen(
                     },
                     ..default()
                 });
+                if let Some(score) = scoring.score.as_ref() {
+                    let best = scoring.best.as_ref();
+                    for (name, value, best) in [
+                        ("Cells", score.cells, best.map(|best| best.cells)),
+                        ("Ticks", score.ticks, best.map(|best| best.ticks)),
+                        ("Area", score.area, best.map(|best| best.area)),
+                    ] {
+                        parent.spawn(TextBundle::from_section(score_line(name, value, best), score_style.clone()));
+                    }
+                }
                 parent.spawn(NodeBundle {
                     style: Style {
                         width: Val::Px(400.0),
@@ -190,11 +306,23 @@ pub fn setup_finish_screen(
                 }).with_children(|parent| {
                     spawn_button(parent, texture_atlas_handle.clone(), 9, LevelActions::Restart);
                     spawn_button(parent, texture_atlas_handle.clone(), 11, LevelActions::Menu);
+                    if let Some(next_level) = next_level {
+                        spawn_button(parent, texture_atlas_handle.clone(), 32, LevelActions::NextLevel(next_level));
+                    }
                 });
             });
         });
 }
 
+/// Number of the solution next to the personal best before it.
+fn score_line(name: &str, value: usize, best: Option<usize>) -> String {
+    match best {
+        Some(best) if value < best => format!("{name}: {value}, new best (was {best})"),
+        Some(best) => format!("{name}: {value}, best {best}"),
+        None => format!("{name}: {value}, first solution"),
+    }
+}
+
 pub fn delete_ui<T: Component>(
     to_despawn: Query<Entity, With<T>>,
     mut commands: Commands,
@@ -207,41 +335,374 @@ pub fn delete_ui<T: Component>(
 pub fn button_click(
     mut actions: EventReader<LevelActions>,
     mut counter: ResMut<Counter>,
-    world: Option<ResMut<WorldState>>,
+    mut world: Option<ResMut<WorldState>>,
     mut level_state: ResMut<NextState<LevelState>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut events: EventWriter<ChangeExercise>,
+    mut history: ResMut<EditHistory>,
+    mut changes: EventWriter<Change>,
+    mut toolbox: ResMut<Toolbox>,
+    mut edits: EventWriter<Edit>,
+    mut stepper: ResMut<Stepper>,
+    mut open_level: EventWriter<OpenLevel>,
+    mut fit_events: EventWriter<FitBoard>,
 ) {
-    if let Some(mut world) = world {
-        for action in actions.iter() {
-            match action {
-                LevelActions::Menu => {
-                    info!("goto menu");
-                    counter.timer.pause();
-                    level_state.set(LevelState::Process);
-                    game_state.set(GameState::LevelsList);
-                }
-                LevelActions::Pause => {
-                    counter.timer.pause();
-                    info!("Pause");
+    for action in actions.iter() {
+        match action {
+            LevelActions::Tool(tool) => {
+                toolbox.tool = *tool;
+                toolbox.stroke = None;
+                toolbox.pasting = false;
+                info!("{:?}", tool);
+            }
+            LevelActions::Patterns | LevelActions::Stamp(_) | LevelActions::SavePattern | LevelActions::Analyzer
+            | LevelActions::Dismiss => {}
+            LevelActions::RunTo => {
+                stepper.input = match stepper.input {
+                    Some(_) => None,
+                    None => Some(String::new()),
+                };
+            }
+            LevelActions::Paste => {
+                toolbox.pasting = toolbox.clipboard.is_some();
+                info!("{:?}", action);
+            }
+            LevelActions::Rotate | LevelActions::MirrorHorizontal | LevelActions::MirrorVertical => {
+                if let Some(pattern) = toolbox.clipboard.as_mut() {
+                    match action {
+                        LevelActions::Rotate => pattern.rotate(),
+                        LevelActions::MirrorHorizontal => pattern.mirror_horizontal(),
+                        _others => pattern.mirror_vertical(),
+                    }
                 }
-                LevelActions::Play(speed) => {
+                info!("{:?}", action);
+            }
+            LevelActions::Menu => {
+                info!("goto menu");
+                counter.timer.pause();
+                level_state.set(LevelState::Process);
+                game_state.set(GameState::LevelsList);
+            }
+            LevelActions::NextLevel(file_name) => {
+                info!("goto next level {}", file_name);
+                counter.timer.pause();
+                level_state.set(LevelState::Process);
+                open_level.send(OpenLevel(file_name.clone()));
+            }
+            LevelActions::FitBoard => {
+                fit_events.send(FitBoard);
+                info!("Fit board");
+            }
+            _others if world.is_none() => {}
+            LevelActions::Pause => {
+                counter.timer.pause();
+                info!("Pause");
+            }
+            LevelActions::Play(speed) => {
+                if let Some(world) = world.as_mut() {
                     counter.timer.unpause();
                     counter.timer.set_duration(Duration::from_secs_f32(*speed));
                     world.lock = world.exercises.len() > 0;
                     info!("set speed {}", speed);
                 }
-                LevelActions::Restart => {
-                    level_state.set(LevelState::Process);
+            }
+            LevelActions::Step => {
+                if let Some(world) = world.as_mut() {
                     counter.timer.pause();
-                    events.send(ChangeExercise(0));
-                    info!("Reload");
+                    world.lock = !world.exercises.is_empty();
+                    stepper.forward += 1;
+                    info!("Step");
                 }
             }
+            LevelActions::StepBack => {
+                counter.timer.pause();
+                stepper.target = None;
+                stepper.back += 1;
+                info!("Step back");
+            }
+            LevelActions::Restart => {
+                level_state.set(LevelState::Process);
+                counter.timer.pause();
+                events.send(ChangeExercise(0));
+                info!("Reload");
+            }
+            LevelActions::Undo | LevelActions::Redo => {
+                if world.as_ref().map(|world| world.lock).unwrap_or(true) {
+                    continue;
+                }
+                let cells = match action {
+                    LevelActions::Undo => history.undo(),
+                    _others => history.redo(),
+                };
+                for (point, cell_type) in cells.into_iter().flatten() {
+                    changes.send(Change(point, cell_type));
+                }
+                info!("{:?}", action);
+            }
+            LevelActions::Copy | LevelActions::Cut => {
+                let (Some(world), Some((from, to))) = (world.as_ref(), toolbox.selection.clone()) else {
+                    continue;
+                };
+                toolbox.clipboard = Some(Pattern::copy(&from, &to, &world.simulation));
+                if *action == LevelActions::Cut && !world.lock {
+                    edits.send(Edit(clear(&from, &to, &world.simulation)));
+                }
+                info!("{:?}", action);
+            }
+        }
+    }
+}
+
+/// Bound keys play, pause and step the board, choose speeds and go to the menu.
+/// The menu key goes to the menu only when there is no paste, selection or failure to cancel first.
+pub fn level_keys(
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    bindings: Res<KeyBindings>,
+    counter: Res<Counter>,
+    toolbox: Res<Toolbox>,
+    stepper: Res<Stepper>,
+    failure: Option<Res<Failure>>,
+    level_state: Res<State<LevelState>>,
+    mut actions: EventWriter<LevelActions>,
+    mut mouse_used: Local<bool>,
+) {
+    if stepper.input.is_some() {
+        return;
+    }
+    if bindings.just_pressed(&keys, KeyAction::Menu) && !toolbox.pasting && toolbox.selection.is_none() && failure.is_none() {
+        actions.send(LevelActions::Menu);
+    }
+    if *level_state.get() != LevelState::Process {
+        return;
+    }
+
+    // Space also drags the camera with the left button, so the board is played or paused
+    // when the key is released and no button was pressed while it was held.
+    if bindings.pressed(&keys, KeyAction::PlayPause) && mouse.get_pressed().len() > 0 {
+        *mouse_used = true;
+    }
+    if bindings.just_released(&keys, KeyAction::PlayPause) {
+        if !*mouse_used {
+            actions.send(match counter.timer.paused() {
+                true => LevelActions::Play(counter.timer.duration().as_secs_f32()),
+                false => LevelActions::Pause,
+            });
+        }
+        *mouse_used = false;
+    }
+
+    let action = [
+        (KeyAction::Step, LevelActions::Step),
+        (KeyAction::SpeedNormal, LevelActions::Play(1.)),
+        (KeyAction::SpeedFast, LevelActions::Play(0.5)),
+        (KeyAction::SpeedFastest, LevelActions::Play(0.125)),
+    ].into_iter().find(|(action, _)| bindings.just_pressed(&keys, *action));
+    if let Some((_, action)) = action {
+        actions.send(action);
+    }
+}
+
+/// Ctrl+Z undoes the last edit, Ctrl+Shift+Z repeats it.
+pub fn history_keys(
+    keys: Res<Input<KeyCode>>,
+    mut actions: EventWriter<LevelActions>,
+) {
+    let control = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    if control && keys.just_pressed(KeyCode::Z) {
+        actions.send(match shift {
+            true => LevelActions::Redo,
+            false => LevelActions::Undo,
+        });
+    }
+}
+
+/// Ctrl+C, Ctrl+X and Ctrl+V copy, cut and paste the selection, R rotates the clipboard,
+/// H and V mirror it. Escape stops pasting and drops the selection.
+pub fn clipboard_keys(
+    keys: Res<Input<KeyCode>>,
+    mut actions: EventWriter<LevelActions>,
+    mut toolbox: ResMut<Toolbox>,
+    stepper: Res<Stepper>,
+) {
+    if stepper.input.is_some() {
+        return;
+    }
+    let control = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]);
+
+    let action = match control {
+        true if keys.just_pressed(KeyCode::C) => Some(LevelActions::Copy),
+        true if keys.just_pressed(KeyCode::X) => Some(LevelActions::Cut),
+        true if keys.just_pressed(KeyCode::V) => Some(LevelActions::Paste),
+        false if keys.just_pressed(KeyCode::R) => Some(LevelActions::Rotate),
+        false if keys.just_pressed(KeyCode::H) => Some(LevelActions::MirrorHorizontal),
+        false if keys.just_pressed(KeyCode::V) => Some(LevelActions::MirrorVertical),
+        _others => None,
+    };
+    if let Some(action) = action {
+        actions.send(action);
+    }
+
+    if keys.just_pressed(KeyCode::Escape) && (toolbox.pasting || toolbox.selection.is_some()) {
+        toolbox.pasting = false;
+        toolbox.selection = None;
+    }
+}
+
+/// Digits typed while the run to tick field is focused, Enter runs the board forward or back
+/// to the tick of the exercise, Escape leaves the field.
+pub fn type_tick(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut stepper: ResMut<Stepper>,
+    mut counter: ResMut<Counter>,
+    world: Option<ResMut<WorldState>>,
+) {
+    let Some(input) = stepper.input.as_mut() else {
+        characters.clear();
+        return;
+    };
+    for character in characters.iter() {
+        if character.char.is_ascii_digit() && input.len() < 6 {
+            input.push(character.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        input.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        stepper.input = None;
+    } else if keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        if let (Ok(target), Some(mut world)) = (input.parse::<usize>(), world) {
+            counter.timer.pause();
+            world.lock = !world.exercises.is_empty();
+            stepper.target = Some(target);
+            info!("Run to tick {}", target);
+        }
+        stepper.input = None;
+    }
+}
+
+/// Shows the tick of the exercise, the recorded tick chosen on the timeline,
+/// or the typed tick while the run to tick field is focused.
+pub fn tick_label(
+    stepper: Res<Stepper>,
+    recording: Res<Recording>,
+    world: Option<Res<WorldState>>,
+    exercises: Query<&Exercise>,
+    mut labels: Query<&mut Text, With<TickLabel>>,
+) {
+    let ticks = exercises.get_single()
+        .map(|exercise| exercise.ticks)
+        .unwrap_or_else(|_| world.map(|world| world.simulation.ticks()).unwrap_or_default());
+    let value = match (stepper.input.as_ref(), recording.view) {
+        (Some(input), _) => format!("Run to {input}_"),
+        (None, Some(tick)) => format!("Replay {tick}"),
+        (None, None) => format!("Tick {ticks}"),
+    };
+
+    for mut text in labels.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
         }
     }
 }
 
+/// Shows why the level can't be played instead of an empty board.
+pub fn spawn_level_error(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    level_config: Res<LevelConfig>,
+    errors: Res<LevelLoadErrors>,
+    world: Option<Res<WorldState>>,
+    error_ui: Query<Entity, With<LevelErrorUI>>,
+) {
+    if world.is_some() || !error_ui.is_empty() {
+        return;
+    }
+    let (Some(level_name), Some(handle)) = (&level_config.level_name, &level_config.handle) else {
+        return;
+    };
+    if asset_server.get_load_state(handle) != LoadState::Failed {
+        return;
+    }
+    let message = errors.get(level_name)
+        .unwrap_or_else(|| "Level file can't be loaded".to_owned());
+    error!("Failed to load level {}: {}", level_name, message);
+
+    let buttons_handle = asset_server.load("ui/buttons.png");
+    let texture_atlas =
+        TextureAtlas::from_grid(buttons_handle, Vec2::new(16.0, 16.0),
+                                6, 6, None, None);
+    let texture_atlas_handle = texture_atlases.add(texture_atlas);
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let title_style = TextStyle {
+        font: font.clone(),
+        font_size: 32.0,
+        color: Color::WHITE,
+    };
+    let message_style = TextStyle {
+        font,
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::BLACK.with_a(0.4).into(),
+                ..default()
+            },
+            LevelErrorUI::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(640.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        row_gap: Val::Px(20.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    background_color: Color::MAROON.into(),
+                    ..default()
+                }
+            ).with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    format!("Level {} is broken", level_name),
+                    title_style,
+                ));
+                parent.spawn(TextBundle {
+                    text: Text {
+                        sections: vec![TextSection::new(message, message_style)],
+                        alignment: TextAlignment::Left,
+                        linebreak_behavior: BreakLineOn::WordBoundary,
+                    },
+                    style: Style {
+                        max_width: Val::Px(600.0),
+                        ..default()
+                    },
+                    ..default()
+                });
+                spawn_button(parent, texture_atlas_handle.clone(), 11, LevelActions::Menu);
+            });
+        });
+}
+
 fn spawn_button(
     parent: &mut ChildBuilder,
     atlas_handle: Handle<TextureAtlas>,
@@ -4,26 +4,36 @@ use bevy::prelude::*;
 use bevy_tweening::{Animator, EaseFunction, RepeatStrategy, Tween};
 
 use bevy_tweening::lens::UiPositionLens;
-use crate::{GameState, LevelDescription, LEVELS};
+use crate::{GameState, LevelDescription};
 use crate::control::ExitGame;
-use crate::ui::component::{ButtonState, LevelMenuUI, LevelsListNode, MenuActions};
+use crate::control::bindings::{KeyAction, KeyBindings};
+use crate::control::touch::SWIPE_DISTANCE;
+use crate::ui::component::{ButtonState, LevelCard, LevelMenuUI, LevelsListNode, MenuActions};
 
-use crate::world::resources::{LevelConfig};
+use crate::world::progress::Progress;
+use crate::world::resources::{LevelConfig, LevelsCatalog, World};
 
+/// Width of a level card with its margins, the list scrolls by one card.
+const CARD_WIDTH: f32 = 300.0;
+
+/// Level card chosen with the keyboard.
+#[derive(Resource, Default)]
+pub struct MenuFocus(pub Option<usize>);
 
 pub fn spawn_level_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut focus: ResMut<MenuFocus>,
 ) {
+    focus.0 = None;
     let buttons_handle = asset_server.load("ui/buttons.png");
     let texture_atlas =
         TextureAtlas::from_grid(buttons_handle, Vec2::new(16.0, 16.0),
-                                6, 2, None, None);
+                                6, 6, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
 
-    #[cfg(not(target_arch = "wasm32"))]
     commands.spawn((
         ButtonBundle {
             style: Style {
@@ -54,6 +64,34 @@ pub fn spawn_level_menu(
         });
     });
 
+    commands.spawn((
+        ButtonBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(25.0),
+                top: Val::Px(25.0),
+                height: Val::Px(64.0),
+                padding: UiRect::horizontal(Val::Px(16.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        ButtonState::default(),
+        MenuActions::Edit(None),
+        LevelMenuUI::default(),
+    )).with_children(|builder| {
+        builder.spawn(TextBundle::from_section(
+            "New level",
+            TextStyle {
+                font: font.clone(),
+                font_size: 32.0,
+                color: Color::WHITE,
+            },
+        ));
+    });
+
     commands
         .spawn((
             NodeBundle {
@@ -167,11 +205,7 @@ pub fn spawn_level_menu(
                                     end: UiRect::left(Val::Px(0.0)),
                                 },
                             )),
-                        )).with_children(|builder| {
-                            for level in LEVELS {
-                                spawn_level_button(builder, font.clone(), level);
-                            }
-                        });
+                        ));
                     });
                     builder.spawn((
                         ButtonBundle {
@@ -205,12 +239,58 @@ pub fn spawn_level_menu(
         });
 }
 
+/// Rebuilds level cards when the manifest or any level file is (re)loaded, or a level is solved.
+/// Levels whose prerequisites aren't solved are shown locked and can't be opened.
+pub fn fill_levels_list(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    catalog: Res<LevelsCatalog>,
+    progress: Res<Progress>,
+    levels: Res<Assets<World>>,
+    mut levels_events: EventReader<AssetEvent<World>>,
+    spawned_list: Query<Entity, Added<LevelsListNode>>,
+    level_list: Query<Entity, With<LevelsListNode>>,
+) {
+    let levels_changed = levels_events.iter().count() > 0;
+    if !catalog.is_changed() && !progress.is_changed() && !levels_changed && spawned_list.is_empty() {
+        return;
+    }
+
+    if let Ok(level_list) = level_list.get_single() {
+        let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+        let buttons_handle = asset_server.load("ui/buttons.png");
+        let texture_atlas =
+            TextureAtlas::from_grid(buttons_handle, Vec2::new(16.0, 16.0),
+                                    6, 6, None, None);
+        let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+        let mut level_list = commands.entity(level_list);
+        level_list.despawn_descendants();
+        level_list.with_children(|builder| {
+            let loaded = catalog.levels.iter()
+                .filter_map(|(file_name, handle)| levels.get(handle).map(|world| (file_name, world)));
+            for (index, (file_name, world)) in loaded.enumerate() {
+                spawn_level_button(builder, font.clone(), texture_atlas_handle.clone(),
+                                   LevelDescription::new(file_name, world), index, &catalog, &progress);
+            }
+        });
+    }
+}
+
 fn spawn_level_button(
     builder: &mut ChildBuilder,
     font: Handle<Font>,
+    atlas_handle: Handle<TextureAtlas>,
     description: LevelDescription,
+    index: usize,
+    catalog: &LevelsCatalog,
+    progress: &Progress,
 ) {
-    builder.spawn((
+    let solved = progress.is_solved(&description.file_name);
+    let unlocked = catalog.packs.is_unlocked(&description.file_name, progress);
+    let pack = catalog.packs.pack(&description.file_name).map(|pack| pack.name.clone());
+    let mut card = builder.spawn((
         ButtonBundle {
             style: Style {
                 width: Val::Px(250.0),
@@ -226,31 +306,90 @@ fn spawn_level_button(
             ..default()
         },
         ButtonState::default(),
-        MenuActions::Level(description.file_name.to_owned()),
-    )).with_children(|builder| {
+        LevelCard(index),
+    ));
+    if unlocked {
+        card.insert(MenuActions::Level(description.file_name.clone()));
+    }
+    card.with_children(|builder| {
+        let text_color = match unlocked {
+            true => Color::WHITE,
+            false => Color::GRAY,
+        };
         let primary_style = TextStyle {
             font: font.clone(),
             font_size: 48.0,
-            color: Color::WHITE,
+            color: text_color,
         };
         let secondary_style = TextStyle {
             font: font.clone(),
             font_size: 32.0,
-            color: Color::WHITE,
+            color: text_color,
         };
 
+        let size: String = description.size.into();
         builder.spawn(TextBundle::from_section(
-            description.size,
+            match pack {
+                Some(pack) => format!("{pack} \u{2022} {size}"),
+                None => size,
+            },
             secondary_style.clone(),
         ));
         builder.spawn(TextBundle::from_section(
-            description.title.to_owned(),
+            description.title,
             primary_style.clone(),
         ));
         builder.spawn(TextBundle::from_section(
             format!("{} exercises", description.exercise_count),
             secondary_style.clone(),
         ));
+        spawn_edit_button(builder, secondary_style, MenuActions::Edit(Some(description.file_name)));
+        let icon = match (solved, unlocked) {
+            (true, _) => Some(30),
+            (false, false) => Some(31),
+            _others => None,
+        };
+        if let Some(index) = icon {
+            builder.spawn(AtlasImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(12.0),
+                    right: Val::Px(12.0),
+                    width: Val::Px(32.0),
+                    height: Val::Px(32.0),
+                    ..default()
+                },
+                texture_atlas: atlas_handle,
+                texture_atlas_image: UiTextureAtlasImage {
+                    index,
+                    flip_x: false,
+                    flip_y: false,
+                },
+                ..default()
+            });
+        }
+    });
+}
+
+fn spawn_edit_button(
+    builder: &mut ChildBuilder,
+    text_style: TextStyle,
+    action: MenuActions,
+) {
+    builder.spawn((
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        ButtonState::default(),
+        action,
+    )).with_children(|builder| {
+        builder.spawn(TextBundle::from_section("Edit", text_style));
     });
 }
 
@@ -263,27 +402,28 @@ pub fn button_system(
         (&Interaction, &mut BackgroundColor, &mut ButtonState, &MenuActions),
         (Changed<Interaction>, With<Button>),
     >,
+    touches: Res<Touches>,
     mut actions: EventWriter<MenuActions>,
 ) {
     for (interaction, _color, mut state, action)
     in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Hovered => {
-                if state.prev_interaction == Interaction::Pressed {
-                    actions.send(action.clone());
-                }
-            }
-            _other => {}
+        if state.clicked(*interaction, &touches) {
+            actions.send(action.clone());
         }
         state.prev_interaction = interaction.clone();
     }
 }
 
 pub fn button_state(
-    mut buttons: Query<(&mut BackgroundColor, &mut ButtonState), With<Button>>,
+    mut buttons: Query<(&mut BackgroundColor, &mut ButtonState, Option<&LevelCard>), With<Button>>,
+    focus: Res<MenuFocus>,
 ) {
-    for (mut color, button_state) in buttons.iter_mut() {
+    for (mut color, button_state, card) in buttons.iter_mut() {
+        let focused = card.map(|card| Some(card.0) == focus.0).unwrap_or_default();
         match button_state.prev_interaction {
+            Interaction::None if focused => {
+                *color = HOVERED_BUTTON.into();
+            }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
             }
@@ -312,6 +452,10 @@ pub fn button_click(
                 level_config.level_name = Some(file_name);
                 game_state.set(GameState::Level);
             }
+            MenuActions::Edit(file_name) => {
+                level_config.level_name = file_name;
+                game_state.set(GameState::Editor);
+            }
             MenuActions::Scroll(delta) => {
                 let (_id, level_list, mut animator, node, parent)
                     = level_list.single_mut();
@@ -323,10 +467,10 @@ pub fn button_click(
                         Duration::from_millis(300),
                         UiPositionLens {
                             start: UiRect::left(level_list.left),
-                            end: UiRect::left(Val::Px(px + (delta as f32) * 300.0)),
+                            end: UiRect::left(Val::Px(px + (delta as f32) * CARD_WIDTH)),
                         },
                     );
-                    if px % 300.0 == 0.0 {
+                    if px % CARD_WIDTH == 0.0 {
                         if px == 0.0 && delta == 1
                             || -px > node.size().x - parent.size().x && delta == -1 {
                             animator.set_tweenable(tween.with_repeat_count(2)
@@ -342,4 +486,78 @@ pub fn button_click(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Bound keys move the focus between level cards, scroll the list to keep the focused card visible
+/// and open its level.
+pub fn menu_keys(
+    keys: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut focus: ResMut<MenuFocus>,
+    cards: Query<(&LevelCard, Option<&MenuActions>)>,
+    level_list: Query<(&Style, &Parent), With<LevelsListNode>>,
+    nodes: Query<&Node>,
+    mut actions: EventWriter<MenuActions>,
+) {
+    let count = cards.iter().count();
+    if count == 0 {
+        return;
+    }
+
+    let delta = match (bindings.just_pressed(&keys, KeyAction::PreviousLevel), bindings.just_pressed(&keys, KeyAction::NextLevel)) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _others => 0,
+    };
+    if delta != 0 {
+        let index = match focus.0 {
+            Some(index) => (index as isize + delta).clamp(0, count as isize - 1) as usize,
+            None => 0,
+        };
+        focus.0 = Some(index);
+
+        if let Ok((style, parent)) = level_list.get_single() {
+            if let (Val::Px(left), Ok(parent)) = (style.left, nodes.get(parent.get())) {
+                let first = (-left / CARD_WIDTH).round().max(0.0) as usize;
+                let visible = ((parent.size().x / CARD_WIDTH).floor() as usize).max(1);
+                if index < first {
+                    actions.send(MenuActions::Scroll(1));
+                } else if index >= first + visible {
+                    actions.send(MenuActions::Scroll(-1));
+                }
+            }
+        }
+    }
+
+    if bindings.just_pressed(&keys, KeyAction::OpenLevel) {
+        let action = cards.iter()
+            .find(|(card, _)| Some(card.0) == focus.0)
+            .and_then(|(_, action)| action);
+        if let Some(action) = action {
+            actions.send(action.clone());
+        }
+    }
+}
+
+/// A finger sliding across the level list scrolls it by a card, like the arrow buttons.
+pub fn swipe_levels(
+    touches: Res<Touches>,
+    level_list: Query<&Parent, With<LevelsListNode>>,
+    nodes: Query<(&Node, &GlobalTransform)>,
+    mut actions: EventWriter<MenuActions>,
+) {
+    let Ok(parent) = level_list.get_single() else {
+        return;
+    };
+    let Ok((node, transform)) = nodes.get(parent.get()) else {
+        return;
+    };
+    let strip = Rect::from_center_size(transform.translation().truncate(), node.size());
+
+    for touch in touches.iter_just_released() {
+        let distance = touch.distance();
+        if strip.contains(touch.start_position()) && distance.x.abs() > SWIPE_DISTANCE.max(distance.y.abs()) {
+            actions.send(MenuActions::Scroll(if distance.x > 0.0 { 1 } else { -1 }));
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::control::MoveCamera;
+use crate::control::camera::{BoardBounds, board_fraction, board_point, view_rect};
+use crate::ui::component::{LevelUI, Minimap, MinimapView};
+use crate::world::board::BoardTexture;
+
+/// Longest side of the minimap in pixels of the window.
+const MINIMAP_SIZE: f32 = 160.0;
+const VIEW_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+
+pub fn spawn_minimap(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(80.0),
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            ..default()
+        },
+        Interaction::default(),
+        Minimap::default(),
+        LevelUI::default(),
+    )).with_children(|parent| {
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: VIEW_COLOR.into(),
+                z_index: ZIndex::Local(1),
+                ..default()
+            },
+            MinimapView::default(),
+        ));
+    });
+}
+
+/// Shows the minimap for boards which don't fit into the window at the cell size and moves the view rectangle.
+/// The texture itself is painted by `BoardTexture` together with the board.
+pub fn update_minimap(
+    board: Option<Res<BoardTexture>>,
+    bounds: Res<BoardBounds>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut minimap: Query<(&mut Style, &mut UiImage), (With<Minimap>, Without<MinimapView>)>,
+    mut views: Query<&mut Style, With<MinimapView>>,
+) {
+    let (Some(board), Some(board_rect), Ok(window), Ok((transform, projection))) =
+        (board, bounds.0, windows.get_single(), camera.get_single()) else {
+        return;
+    };
+    let window = Vec2::new(window.width(), window.height());
+    let view = view_rect(transform.translation.truncate(), projection.scale, window);
+    let display = match board_rect.width() > window.x || board_rect.height() > window.y {
+        true => Display::Flex,
+        false => Display::None,
+    };
+
+    for (mut style, mut image) in minimap.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+        if image.texture != board.minimap() {
+            let (width, height) = board.size();
+            let side = width.max(height).max(1) as f32;
+            style.width = Val::Px(MINIMAP_SIZE * width as f32 / side);
+            style.height = Val::Px(MINIMAP_SIZE * height as f32 / side);
+            image.texture = board.minimap();
+        }
+    }
+    if display == Display::None {
+        return;
+    }
+
+    let top_left = board_fraction(board_rect, Vec2::new(view.min.x, view.max.y)).clamp(Vec2::ZERO, Vec2::ONE);
+    let bottom_right = board_fraction(board_rect, Vec2::new(view.max.x, view.min.y)).clamp(Vec2::ZERO, Vec2::ONE);
+    for mut style in views.iter_mut() {
+        let (left, top) = (Val::Percent(100.0 * top_left.x), Val::Percent(100.0 * top_left.y));
+        let (width, height) = (Val::Percent(100.0 * (bottom_right.x - top_left.x)), Val::Percent(100.0 * (bottom_right.y - top_left.y)));
+        if (style.left, style.top, style.width, style.height) != (left, top, width, height) {
+            style.left = left;
+            style.top = top;
+            style.width = width;
+            style.height = height;
+        }
+    }
+}
+
+/// Pressing or dragging on the minimap with the mouse or a finger centers the camera on that part of the board.
+pub fn minimap_click(
+    bounds: Res<BoardBounds>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    minimap: Query<(&Interaction, &Node, &GlobalTransform), With<Minimap>>,
+    touches: Res<Touches>,
+    mut camera_events: EventWriter<MoveCamera>,
+) {
+    let (Some(board_rect), Ok(window)) = (bounds.0, windows.get_single()) else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position().or_else(|| touches.first_pressed_position()) else {
+        return;
+    };
+
+    for (interaction, node, transform) in minimap.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let size = node.size().max(Vec2::ONE);
+        let top_left = transform.translation().truncate() - size / 2.0;
+        let fraction = ((cursor - top_left) / size).clamp(Vec2::ZERO, Vec2::ONE);
+        camera_events.send(MoveCamera {
+            pos: board_point(board_rect, fraction),
+            force: true,
+            absolute: true,
+        });
+    }
+}
@@ -1,12 +1,19 @@
+pub mod analyzer;
 pub mod component;
+pub mod failure;
 pub mod level;
 pub mod level_menu;
+pub mod minimap;
+pub mod patterns;
+pub mod timeline;
 
 use bevy::app::App;
 use bevy::prelude::*;
 use crate::{GameState, LevelState};
-use crate::ui::component::{LevelActions, LevelFinishUI, LevelMenuUI, LevelUI, MenuActions};
+use crate::ui::component::{LevelActions, LevelErrorUI, LevelFinishUI, LevelMenuUI, LevelUI, MenuActions};
 use crate::ui::level::{button_click, button_state, button_system};
+use crate::ui::level_menu::MenuFocus;
+use crate::world::services::{keyboard_cursor, score_level};
 
 pub struct UiPlugin;
 
@@ -16,26 +23,55 @@ impl Plugin for UiPlugin {
             .add_state::<LevelState>()
             .add_event::<LevelActions>()
             .add_event::<MenuActions>()
-            .add_systems(OnEnter(GameState::Level), level::setup)
+            .init_resource::<MenuFocus>()
+            .add_systems(Startup, patterns::load_user_patterns)
+            .add_systems(OnEnter(GameState::Level), (level::setup, patterns::spawn_pattern_panel, timeline::spawn_timeline, analyzer::spawn_analyzer_panel, minimap::spawn_minimap))
             .add_systems(Update, (
                 button_state.after(button_system),
                 button_system,
                 button_click,
+                level::history_keys.before(button_click),
+                level::clipboard_keys.before(button_click),
+                level::type_tick.after(level::clipboard_keys).after(keyboard_cursor),
+                level::tick_label,
+                level::spawn_level_error,
+                patterns::fill_pattern_panel,
+                patterns::pattern_click,
+                timeline::fill_timeline_markers,
+                timeline::update_timeline,
+                timeline::scrub_timeline,
+                analyzer::fill_analyzer_panel,
+                analyzer::update_waveforms.after(analyzer::fill_analyzer_panel),
+                analyzer::analyzer_click,
+                failure::spawn_failure_message,
+                failure::dismiss_failure,
+                minimap::update_minimap,
+                minimap::minimap_click,
             ).run_if(in_state(GameState::Level)))
+            .add_systems(Update, level::level_keys
+                .before(level::clipboard_keys)
+                .before(level::type_tick)
+                .before(failure::dismiss_failure)
+                .before(button_click)
+                .run_if(in_state(GameState::Level)))
             .add_systems(OnExit(GameState::Level), (
                 level::delete_ui::<LevelUI>,
-                level::delete_ui::<LevelFinishUI>
+                level::delete_ui::<LevelFinishUI>,
+                level::delete_ui::<LevelErrorUI>,
                 ))
             .add_systems(OnExit(LevelState::Process), level::delete_ui::<LevelUI>)
             .add_systems(OnExit(LevelState::Finish), level::delete_ui::<LevelFinishUI>)
-            .add_systems(OnEnter(LevelState::Finish), level::setup_finish_screen)
-            .add_systems(OnExit(LevelState::Finish), level::setup)
+            .add_systems(OnEnter(LevelState::Finish), level::setup_finish_screen.after(score_level))
+            .add_systems(OnExit(LevelState::Finish), (level::setup, patterns::spawn_pattern_panel, timeline::spawn_timeline, analyzer::spawn_analyzer_panel, minimap::spawn_minimap))
             .add_systems(OnEnter(GameState::LevelsList), level_menu::spawn_level_menu)
             .add_systems(OnExit(GameState::LevelsList), level::delete_ui::<LevelMenuUI>)
             .add_systems(Update, (
                 level_menu::button_state.after(level_menu::button_system),
                 level_menu::button_system,
                 level_menu::button_click,
+                level_menu::fill_levels_list,
+                level_menu::menu_keys.before(level_menu::button_click),
+                level_menu::swipe_levels.before(level_menu::button_click),
             ).run_if(in_state(GameState::LevelsList)))
         ;
     }
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use crate::ui::component::{ButtonState, LevelActions, LevelUI, PatternPanel};
+use crate::world::clipboard::Pattern;
+use crate::world::library::PatternLibrary;
+use crate::world::resources::WorldState;
+use crate::world::tools::Toolbox;
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+const FONT_SIZE: f32 = 20.0;
+
+pub fn spawn_pattern_panel(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                right: Val::Px(0.0),
+                top: Val::Px(70.0),
+                bottom: Val::Px(70.0),
+                width: Val::Px(220.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(6.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: Color::BLACK.with_a(0.7).into(),
+            ..default()
+        },
+        Interaction::default(),
+        PatternPanel::default(),
+        LevelUI::default(),
+    ));
+}
+
+/// Lists the library again when a pattern is saved or the panel is spawned.
+pub fn fill_pattern_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    library: Res<PatternLibrary>,
+    spawned_panel: Query<Entity, Added<PatternPanel>>,
+    panel: Query<Entity, With<PatternPanel>>,
+) {
+    if !library.is_changed() && spawned_panel.is_empty() {
+        return;
+    }
+
+    if let Ok(panel) = panel.get_single() {
+        let style = TextStyle {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: FONT_SIZE,
+            color: Color::WHITE,
+        };
+
+        let mut panel = commands.entity(panel);
+        panel.despawn_descendants();
+        panel.with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Patterns", style.clone()));
+            for (index, named) in library.patterns.iter().enumerate() {
+                let (width, height) = named.pattern.size;
+                spawn_text_button(parent, &style, format!("{} {width}x{height}", named.name), LevelActions::Stamp(index));
+            }
+            spawn_text_button(parent, &style, "Save selection", LevelActions::SavePattern);
+            if !library.status.is_empty() {
+                parent.spawn(TextBundle::from_section(library.status.clone(), style.clone()));
+            }
+        });
+    }
+}
+
+pub fn pattern_click(
+    mut actions: EventReader<LevelActions>,
+    mut library: ResMut<PatternLibrary>,
+    mut toolbox: ResMut<Toolbox>,
+    world: Option<Res<WorldState>>,
+    mut panels: Query<&mut Style, With<PatternPanel>>,
+) {
+    for action in actions.iter() {
+        match action {
+            LevelActions::Patterns => {
+                for mut style in panels.iter_mut() {
+                    style.display = match style.display {
+                        Display::None => Display::Flex,
+                        _others => Display::None,
+                    };
+                }
+            }
+            LevelActions::Stamp(index) => {
+                if let Some(named) = library.patterns.get(*index) {
+                    info!("Stamp {}", named.name);
+                    toolbox.clipboard = Some(named.pattern.clone());
+                    toolbox.pasting = true;
+                    toolbox.stroke = None;
+                }
+            }
+            LevelActions::SavePattern => {
+                if let (Some(world), Some((from, to))) = (world.as_ref(), toolbox.selection.as_ref()) {
+                    let name = library.add(Pattern::copy(from, to, &world.simulation));
+                    save_patterns(&mut library, &name);
+                }
+            }
+            _others => {}
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_patterns(library: &mut PatternLibrary, name: &str) {
+    library.status = match write_patterns(library) {
+        Ok(()) => {
+            info!("Pattern {} is saved", name);
+            format!("Saved {name}")
+        }
+        Err(error) => {
+            error!("Failed to save pattern {}: {}", name, error);
+            format!("Failed to save {name}: {error}")
+        }
+    };
+}
+
+/// Writes patterns of the player next to the assets directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_patterns(library: &PatternLibrary) -> anyhow::Result<()> {
+    use std::fs;
+    use bevy::asset::FileAssetIo;
+    use crate::world::library::USER_PATTERNS_FILE;
+
+    fs::write(FileAssetIo::get_base_path().join(USER_PATTERNS_FILE), library.user_to_ron()?)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_patterns(library: &mut PatternLibrary, name: &str) {
+    library.status = format!("{name} is kept until the page is closed");
+}
+
+/// Reads patterns saved by the player in previous sessions.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_user_patterns(
+    mut library: ResMut<PatternLibrary>,
+) {
+    use std::fs;
+    use bevy::asset::FileAssetIo;
+    use crate::world::library::{parse_patterns, USER_PATTERNS_FILE};
+
+    let path = FileAssetIo::get_base_path().join(USER_PATTERNS_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+    match parse_patterns(&content) {
+        Ok(patterns) => {
+            info!("Loaded {} patterns from {:?}", patterns.len(), path);
+            library.patterns.extend(patterns);
+        }
+        Err(error) => {
+            error!("Failed to load patterns from {:?}: {}", path, error);
+            library.status = format!("Saved patterns are broken: {error}");
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_user_patterns() {}
+
+pub fn spawn_text_button(parent: &mut ChildBuilder, style: &TextStyle, text: impl Into<String>, action: LevelActions) {
+    parent.spawn((
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            background_color: NORMAL_BUTTON.into(),
+            ..default()
+        },
+        ButtonState::default(),
+        action,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(text, style.clone()));
+    });
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::ui::component::{LevelUI, Timeline, TimelineFill, TimelineHead, TimelineMarker};
+use crate::world::recording::Recording;
+use crate::world::resources::{Counter, WorldState};
+use crate::world::stepper::Stepper;
+
+const SPAWN_MARKER: Color = Color::YELLOW;
+const EXPECTED_ELECTRON: Color = Color::LIME_GREEN;
+const EXPECTED_SILENCE: Color = Color::ORANGE_RED;
+
+pub fn spawn_timeline(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(75.0),
+                left: Val::Percent(10.0),
+                width: Val::Percent(80.0),
+                height: Val::Px(24.0),
+                ..default()
+            },
+            background_color: Color::BLACK.with_a(0.7).into(),
+            ..default()
+        },
+        Interaction::default(),
+        Timeline::default(),
+        LevelUI::default(),
+    )).with_children(|parent| {
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                ..default()
+            },
+            TimelineFill::default(),
+        ));
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(3.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: Color::WHITE.into(),
+                z_index: ZIndex::Local(1),
+                ..default()
+            },
+            TimelineHead::default(),
+        ));
+    });
+}
+
+/// Ticks covered by the timeline: the timeout of the exercise or the whole recording if it is longer.
+fn timeline_length(recording: &Recording, world: &WorldState) -> usize {
+    let timeout = recording.exercise
+        .and_then(|exercise| world.exercises.get(exercise))
+        .map(|exercise| exercise.timeout + 1)
+        .unwrap_or_default();
+    timeout.max(recording.len()).max(1)
+}
+
+fn percent(tick: usize, length: usize) -> Val {
+    Val::Percent(100.0 * tick.min(length) as f32 / length as f32)
+}
+
+/// Places markers of the recorded exercise again when a new run starts.
+pub fn fill_timeline_markers(
+    mut commands: Commands,
+    recording: Res<Recording>,
+    world: Option<Res<WorldState>>,
+    timeline: Query<Entity, With<Timeline>>,
+    spawned_timeline: Query<Entity, Added<Timeline>>,
+    markers: Query<Entity, With<TimelineMarker>>,
+    mut shown: Local<Option<(usize, usize)>>,
+) {
+    let (Some(world), Ok(timeline)) = (world, timeline.get_single()) else {
+        return;
+    };
+    let Some(exercise_id) = recording.exercise else {
+        return;
+    };
+    let length = timeline_length(&recording, &world);
+    if *shown == Some((exercise_id, length)) && spawned_timeline.is_empty() {
+        return;
+    }
+    *shown = Some((exercise_id, length));
+
+    for marker in markers.iter() {
+        commands.entity(marker).despawn_recursive();
+    }
+    let Some(exercise) = world.exercises.get(exercise_id) else {
+        return;
+    };
+    commands.entity(timeline).with_children(|parent| {
+        for (_, expectation, from, until) in exercise.outputs.iter() {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: percent(*from, length),
+                        width: percent(until.saturating_sub(*from), length),
+                        top: Val::Percent(60.0),
+                        height: Val::Percent(40.0),
+                        ..default()
+                    },
+                    background_color: match expectation {
+                        true => EXPECTED_ELECTRON,
+                        false => EXPECTED_SILENCE,
+                    }.with_a(0.8).into(),
+                    ..default()
+                },
+                TimelineMarker::default(),
+            ));
+        }
+        for (_, instant) in exercise.spawns.iter() {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: percent(*instant, length),
+                        width: Val::Px(3.0),
+                        height: Val::Percent(60.0),
+                        ..default()
+                    },
+                    background_color: SPAWN_MARKER.into(),
+                    ..default()
+                },
+                TimelineMarker::default(),
+            ));
+        }
+    });
+}
+
+/// Shows the timeline once a run is recorded and moves the head to the shown tick.
+pub fn update_timeline(
+    recording: Res<Recording>,
+    world: Option<Res<WorldState>>,
+    mut timeline: Query<&mut Style, (With<Timeline>, Without<TimelineFill>, Without<TimelineHead>)>,
+    mut fill: Query<&mut Style, (With<TimelineFill>, Without<TimelineHead>)>,
+    mut head: Query<&mut Style, With<TimelineHead>>,
+) {
+    let Some(world) = world else {
+        return;
+    };
+    let length = timeline_length(&recording, &world);
+
+    for mut style in timeline.iter_mut() {
+        let display = match recording.is_empty() {
+            true => Display::None,
+            false => Display::Flex,
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+    for mut style in fill.iter_mut() {
+        style.width = percent(recording.len(), length);
+    }
+    for mut style in head.iter_mut() {
+        style.left = percent(recording.view.unwrap_or(recording.len()), length);
+    }
+}
+
+/// Pressing or dragging on the timeline pauses the board and shows the recorded tick under the cursor.
+pub fn scrub_timeline(
+    mut recording: ResMut<Recording>,
+    mut counter: ResMut<Counter>,
+    mut stepper: ResMut<Stepper>,
+    world: Option<Res<WorldState>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    timeline: Query<(&Interaction, &Node, &GlobalTransform), With<Timeline>>,
+    touches: Res<Touches>,
+) {
+    let (Some(world), Ok(window)) = (world, windows.get_single()) else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position().or_else(|| touches.first_pressed_position()) else {
+        return;
+    };
+
+    for (interaction, node, transform) in timeline.iter() {
+        if *interaction != Interaction::Pressed || recording.is_empty() {
+            continue;
+        }
+        let width = node.size().x.max(1.0);
+        let left = transform.translation().x - width / 2.0;
+        let fraction = ((cursor.x - left) / width).clamp(0.0, 1.0);
+        let tick = (fraction * timeline_length(&recording, &world) as f32).round() as usize;
+
+        let tick = tick.min(recording.len());
+        counter.timer.pause();
+        stepper.target = None;
+        if recording.view != Some(tick) {
+            recording.view = Some(tick);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::{GamePlugin, GameState, LevelState};
+use crate::world::components::OpenLevel;
+use crate::world::resources::{Counter, LevelConfig};
+
+/// Event dispatched on the browser window when the player closes the game, the page shows its own UI again.
+pub const EXIT_EVENT: &str = "wire-world-exit";
+
+/// Screen asked for by the page, `open_requested` takes it on the next frame.
+static REQUESTED: Mutex<Option<Request>> = Mutex::new(None);
+/// The event loop of the browser can be started once, later calls of `start` reuse the running game.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+enum Request {
+    Menu,
+    Level(String),
+}
+
+pub struct WebPlugin;
+
+impl Plugin for WebPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, open_requested);
+    }
+}
+
+/// Starts the game in the `#render` canvas of the page, or shows another screen of the running game.
+/// `level` is a file name from `levels.manifest`, the level menu is opened without it.
+///
+/// The first call never returns: the event loop takes over the page by throwing a JavaScript exception.
+#[wasm_bindgen]
+pub fn start(level: Option<String>) {
+    *REQUESTED.lock().unwrap() = Some(match level {
+        Some(level) => Request::Level(level),
+        None => Request::Menu,
+    });
+    if !STARTED.swap(true, Ordering::SeqCst) {
+        GamePlugin::start();
+    }
+}
+
+pub fn open_requested(
+    state: Res<State<GameState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut level_state: ResMut<NextState<LevelState>>,
+    mut level_config: ResMut<LevelConfig>,
+    mut counter: ResMut<Counter>,
+    mut open_level: EventWriter<OpenLevel>,
+) {
+    let Some(request) = REQUESTED.lock().unwrap().take() else {
+        return;
+    };
+
+    match (request, state.get()) {
+        (Request::Level(level), GameState::Level) => {
+            info!("Page opens level {}", level);
+            counter.timer.pause();
+            level_state.set(LevelState::Process);
+            open_level.send(OpenLevel(level));
+        }
+        (Request::Level(level), _others) => {
+            info!("Page opens level {}", level);
+            level_config.level_name = Some(level);
+            game_state.set(GameState::Level);
+        }
+        (Request::Menu, _others) => {
+            game_state.set(GameState::LevelsList);
+        }
+    }
+}
+
+/// Tells the page that the player closed the game.
+pub fn exit_to_page() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let dispatched = web_sys::Event::new(EXIT_EVENT)
+        .and_then(|event| window.dispatch_event(&event));
+    if let Err(error) = dispatched {
+        error!("Failed to dispatch {}: {:?}", EXIT_EVENT, error);
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use bevy::sprite::Anchor;
+
+use crate::world::CELL_SIZE;
+use crate::world::components::{Boundary, Point};
+use crate::world::simulation::Simulation;
+
+pub const CHUNK_SIZE: usize = 64;
+const EDGE_WIDTH: f32 = 6.;
+const EDGE_COLOR: Color = Color::ORANGE_RED;
+
+#[derive(Component, Default)]
+pub struct BoardChunk;
+
+/// Board is drawn by textures where one pixel is one cell.
+/// The board is split into chunks, so only chunks with changed cells are uploaded again.
+/// The whole board is also painted into one minimap texture.
+#[derive(Resource)]
+pub struct BoardTexture {
+    size: (usize, usize),
+    columns: usize,
+    chunks: Vec<Handle<Image>>,
+    minimap: Handle<Image>,
+}
+
+fn board_image(width: usize, height: usize, color: impl Fn(usize, usize) -> Color) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+
+    for y in 0..height {
+        for x in 0..width {
+            let offset = (y * width + x) * 4;
+            image.data[offset..offset + 4].copy_from_slice(&color(x, y).as_rgba_u8());
+        }
+    }
+
+    image
+}
+
+impl BoardTexture {
+    pub fn spawn(
+        simulation: &Simulation,
+        commands: &mut Commands,
+        images: &mut Assets<Image>,
+    ) -> BoardTexture {
+        let size = simulation.size();
+        let columns = size.0.div_ceil(CHUNK_SIZE);
+        let rows = size.1.div_ceil(CHUNK_SIZE);
+        let mut chunks = Vec::with_capacity(columns * rows);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let width = CHUNK_SIZE.min(size.0 - column * CHUNK_SIZE);
+                let height = CHUNK_SIZE.min(size.1 - row * CHUNK_SIZE);
+
+                let image = board_image(width, height, |x, y| {
+                    simulation.get_cell(&Point(column * CHUNK_SIZE + x, row * CHUNK_SIZE + y)).base_color()
+                });
+
+                let handle = images.add(image);
+                commands.spawn((
+                    BoardChunk::default(),
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(
+                                width as f32 * CELL_SIZE,
+                                height as f32 * CELL_SIZE,
+                            )),
+                            anchor: Anchor::TopLeft,
+                            ..default()
+                        },
+                        texture: handle.clone(),
+                        transform: Transform::from_translation(
+                            Vec3::new(
+                                (column * CHUNK_SIZE) as f32 * CELL_SIZE - CELL_SIZE / 2.,
+                                -((row * CHUNK_SIZE) as f32 * CELL_SIZE) + CELL_SIZE / 2.,
+                                0.,
+                            )
+                        ),
+                        ..default()
+                    },
+                ));
+                chunks.push(handle);
+            }
+        }
+        spawn_dead_edges(simulation.boundary(), size, commands);
+        let minimap = images.add(board_image(size.0, size.1, |x, y| simulation.get_cell(&Point(x, y)).base_color()));
+
+        BoardTexture {
+            size,
+            columns,
+            chunks,
+            minimap,
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn minimap(&self) -> Handle<Image> {
+        self.minimap.clone()
+    }
+
+    pub fn paint(&self, images: &mut Assets<Image>, point: &Point, color: Color) {
+        let column = point.0 / CHUNK_SIZE;
+        let row = point.1 / CHUNK_SIZE;
+        let width = CHUNK_SIZE.min(self.size.0 - column * CHUNK_SIZE);
+
+        if let Some(image) = images.get_mut(&self.chunks[row * self.columns + column]) {
+            let offset = ((point.1 % CHUNK_SIZE) * width + point.0 % CHUNK_SIZE) * 4;
+            image.data[offset..offset + 4].copy_from_slice(&color.as_rgba_u8());
+        }
+        if let Some(image) = images.get_mut(&self.minimap) {
+            let offset = (point.1 * self.size.0 + point.0) * 4;
+            image.data[offset..offset + 4].copy_from_slice(&color.as_rgba_u8());
+        }
+    }
+}
+
+/// Cell under a point of the world, `None` if the point is outside of the board.
+pub fn cell_at(pos: Vec2, size: (usize, usize)) -> Option<Point> {
+    let half_cell_size = CELL_SIZE / 2.;
+    let x = (pos.x + half_cell_size) / CELL_SIZE;
+    let y = (-pos.y + half_cell_size) / CELL_SIZE;
+
+    if x < 0. || x >= size.0 as f32 || y < 0. || y >= size.1 as f32 {
+        return None;
+    }
+
+    Some(Point(x.trunc() as usize, y.trunc() as usize))
+}
+
+/// Center of a cell in the world.
+pub fn cell_center(point: &Point) -> Vec2 {
+    Vec2::new(point.0 as f32 * CELL_SIZE, -(point.1 as f32) * CELL_SIZE)
+}
+
+/// Rectangle which the cells of a board of the given size cover in the world.
+pub fn board_rect(size: (usize, usize)) -> Rect {
+    let half_cell_size = CELL_SIZE / 2.;
+    Rect::new(
+        -half_cell_size,
+        half_cell_size,
+        size.0 as f32 * CELL_SIZE - half_cell_size,
+        -(size.1 as f32) * CELL_SIZE + half_cell_size,
+    )
+}
+
+/// Marks edges which aren't connected to the opposite side of the board.
+fn spawn_dead_edges(boundary: Boundary, size: (usize, usize), commands: &mut Commands) {
+    let width = size.0 as f32 * CELL_SIZE;
+    let height = size.1 as f32 * CELL_SIZE;
+    let center = Vec2::new(width - CELL_SIZE, -height + CELL_SIZE) / 2.;
+    let mut edges = Vec::new();
+
+    if !boundary.wraps_x() {
+        let offset = Vec2::new((width + EDGE_WIDTH) / 2., 0.);
+        let edge_size = Vec2::new(EDGE_WIDTH, height + 2. * EDGE_WIDTH);
+        edges.push((center - offset, edge_size));
+        edges.push((center + offset, edge_size));
+    }
+    if !boundary.wraps_y() {
+        let offset = Vec2::new(0., (height + EDGE_WIDTH) / 2.);
+        let edge_size = Vec2::new(width + 2. * EDGE_WIDTH, EDGE_WIDTH);
+        edges.push((center - offset, edge_size));
+        edges.push((center + offset, edge_size));
+    }
+
+    for (position, edge_size) in edges {
+        commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: EDGE_COLOR,
+                custom_size: Some(edge_size),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::from((position, 0.))),
+            ..default()
+        });
+    }
+}
@@ -0,0 +1,184 @@
+use crate::world::components::{CellType, Point};
+use crate::world::components::CellType::{ELECTRON, EMPTY, TAIL, WIRE};
+use crate::world::simulation::Simulation;
+
+/// Free cells of a board region. Fixed cells aren't copied, they are holes which keep
+/// the board under them when the pattern is pasted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub size: (usize, usize),
+    pub cells: Vec<Option<CellType>>,
+}
+
+impl Pattern {
+    /// Copies the rectangle with the given opposite corners.
+    pub fn copy(from: &Point, to: &Point, simulation: &Simulation) -> Pattern {
+        let (left, top, size) = region(from, to);
+        let mut cells = Vec::with_capacity(size.0 * size.1);
+
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                let cell_type = simulation.get_cell(&Point(left + x, top + y));
+                cells.push(match cell_type.is_fixed() {
+                    true => None,
+                    false => Some(cell_type),
+                });
+            }
+        }
+
+        Pattern { size, cells }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&CellType> {
+        self.cells[y * self.size.0 + x].as_ref()
+    }
+
+    /// Turns the pattern by 90 degrees clockwise.
+    pub fn rotate(&mut self) {
+        let (width, height) = self.size;
+        self.remap((height, width), |x, y| (height - 1 - y, x));
+    }
+
+    /// Swaps the left and the right sides.
+    pub fn mirror_horizontal(&mut self) {
+        let (width, height) = self.size;
+        self.remap((width, height), |x, y| (width - 1 - x, y));
+    }
+
+    /// Swaps the top and the bottom sides.
+    pub fn mirror_vertical(&mut self) {
+        let (width, height) = self.size;
+        self.remap((width, height), |x, y| (x, height - 1 - y));
+    }
+
+    /// Cells to change for pasting with the top left corner at the given point. Cells outside
+    /// of the board and fixed cells of the board are skipped, electrons become wires when
+    /// the level doesn't allow them.
+    pub fn place(&self, at: &Point, simulation: &Simulation, electrons: bool) -> Vec<(Point, CellType)> {
+        let (width, height) = simulation.size();
+        let mut changes = Vec::new();
+
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                let point = Point(at.0 + x, at.1 + y);
+                if point.0 >= width || point.1 >= height || simulation.get_cell(&point).is_fixed() {
+                    continue;
+                }
+
+                let cell_type = match (self.get(x, y), electrons) {
+                    (None, _) => continue,
+                    (Some(ELECTRON(_) | TAIL(_)), false) => WIRE(false),
+                    (Some(cell_type), _) => cell_type.clone(),
+                };
+                changes.push((point, cell_type));
+            }
+        }
+
+        changes
+    }
+
+    /// Moves every cell from `(x, y)` to `target(x, y)` in a pattern of the new size.
+    fn remap(&mut self, size: (usize, usize), target: impl Fn(usize, usize) -> (usize, usize)) {
+        let mut cells = vec![None; size.0 * size.1];
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                let (new_x, new_y) = target(x, y);
+                cells[new_y * size.0 + new_x] = self.cells[y * self.size.0 + x].take();
+            }
+        }
+
+        self.size = size;
+        self.cells = cells;
+    }
+}
+
+/// Cells to change for clearing free cells of the rectangle with the given opposite corners.
+pub fn clear(from: &Point, to: &Point, simulation: &Simulation) -> Vec<(Point, CellType)> {
+    let (left, top, size) = region(from, to);
+    let mut changes = Vec::new();
+
+    for y in top..top + size.1 {
+        for x in left..left + size.0 {
+            let point = Point(x, y);
+            if !simulation.get_cell(&point).is_fixed() {
+                changes.push((point, EMPTY(false)));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Left, top and size of the rectangle with the given opposite corners.
+pub fn region(from: &Point, to: &Point) -> (usize, usize, (usize, usize)) {
+    let (left, top) = (from.0.min(to.0), from.1.min(to.1));
+    (left, top, (from.0.max(to.0) - left + 1, from.1.max(to.1) - top + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::resources::World;
+
+    fn simulation(rows: &[&str]) -> Simulation {
+        let level = format!("{} {} dead\ntrue\n{}\n0\n", rows[0].split(' ').count(), rows.len(), rows.join("\n"));
+        Simulation::new(&World::from_string(level).unwrap())
+    }
+
+    #[test]
+    fn copy_skips_fixed_cells() {
+        let simulation = simulation(&["w W e", "a e w"]);
+        let pattern = Pattern::copy(&Point(1, 1), &Point(0, 0), &simulation);
+
+        assert_eq!(pattern.size, (2, 2));
+        assert_eq!(pattern.cells, vec![Some(WIRE(false)), None, Some(ELECTRON(false)), Some(EMPTY(false))]);
+    }
+
+    #[test]
+    fn rotate_and_mirror() {
+        let simulation = simulation(&["w e e", "a e w"]);
+        let mut pattern = Pattern::copy(&Point(0, 0), &Point(2, 1), &simulation);
+        let original = pattern.clone();
+
+        pattern.rotate();
+        assert_eq!(pattern.size, (2, 3));
+        assert_eq!(pattern.get(0, 0), Some(&ELECTRON(false)));
+        assert_eq!(pattern.get(1, 0), Some(&WIRE(false)));
+        assert_eq!(pattern.get(0, 2), Some(&WIRE(false)));
+
+        for _ in 0..3 {
+            pattern.rotate();
+        }
+        assert_eq!(pattern, original);
+
+        pattern.mirror_horizontal();
+        assert_eq!(pattern.get(0, 1), Some(&WIRE(false)));
+        pattern.mirror_vertical();
+        assert_eq!(pattern.get(0, 0), Some(&WIRE(false)));
+        assert_eq!(pattern.get(2, 1), Some(&WIRE(false)));
+    }
+
+    #[test]
+    fn place_keeps_fixed_cells_and_board_edges() {
+        let simulation = simulation(&["e e e", "e W e"]);
+        let pattern = Pattern {
+            size: (2, 2),
+            cells: vec![Some(ELECTRON(false)), None, Some(WIRE(false)), Some(WIRE(false))],
+        };
+
+        assert_eq!(pattern.place(&Point(0, 0), &simulation, true), vec![
+            (Point(0, 0), ELECTRON(false)),
+            (Point(0, 1), WIRE(false)),
+        ]);
+        assert_eq!(pattern.place(&Point(2, 0), &simulation, false), vec![
+            (Point(2, 0), WIRE(false)),
+            (Point(2, 1), WIRE(false)),
+        ]);
+    }
+
+    #[test]
+    fn clear_keeps_fixed_cells() {
+        let simulation = simulation(&["w W", "a e"]);
+        assert_eq!(clear(&Point(0, 0), &Point(1, 1), &simulation).len(), 3);
+    }
+}
@@ -1,12 +1,11 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use anyhow::Error;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::world::components::CellType::*;
 
-#[derive(Component, Debug, Clone)]
-pub struct Cell {
-    pub position: Point,
-    pub cell_type: CellType,
-}
-
 #[derive(Component, Debug, Clone)]
 pub struct ElectronSpawn {
     pub position: Point,
@@ -37,15 +36,46 @@ pub enum CellType {
     TAIL(bool)
 }
 
+/// Sprite which shows a cell of a stroke which isn't painted yet, of the selection or of a paste.
 #[derive(Component, Default)]
-pub struct NextUpdate;
+pub struct StrokePreview;
 
-#[derive(Component)]
-pub struct Change(pub CellType);
+/// Sprite which highlights the cell of the last failure until it is dismissed.
+#[derive(Component, Default)]
+pub struct FailureMark;
+
+/// Outline of the cell chosen with the keyboard.
+#[derive(Component, Default)]
+pub struct CursorMark;
+
+/// Sprite which marks a probed cell on the board, with the probe name as a child.
+#[derive(Component, Default)]
+pub struct ProbeMark;
+
+#[derive(Event)]
+pub struct Change(pub Point, pub CellType);
+
+/// Cells changed by one action of a player, they are undone together.
+#[derive(Event)]
+pub struct Edit(pub Vec<(Point, CellType)>);
 
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
 pub struct Point(pub usize, pub usize);
 
+/// How neighbours are looked up for cells on the edges of a board.
+#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, Default, Serialize, Deserialize)]
+pub enum Boundary {
+    /// Both axes are wrapped, a board is a torus.
+    #[default]
+    Wrap,
+    /// Cells outside of a board are always empty.
+    Dead,
+    /// Only the left and the right edges are connected.
+    WrapX,
+    /// Only the top and the bottom edges are connected.
+    WrapY,
+}
+
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
 pub enum OutputStatus {
     Inactive,
@@ -57,18 +87,74 @@ pub enum OutputStatus {
 #[derive(Event)]
 pub struct ChangeExercise(pub usize);
 
-impl Cell {
+/// Replaces the current level with the level file without leaving the level screen.
+#[derive(Event)]
+pub struct OpenLevel(pub String);
+
+impl ExpectedOutput {
+    /// Updates the status by the cell under the output at the given tick of the exercise.
+    pub fn update(&mut self, ticks: usize, cell: &CellType) {
+        if ticks < self.from {
+            self.status = OutputStatus::Inactive;
+        } else if ticks >= self.from && ticks < self.until {
+            if self.status == OutputStatus::Inactive {
+                self.status = OutputStatus::Waiting;
+            }
+            if let ELECTRON(_) = cell {
+                self.status = match self.expectation {
+                    true => OutputStatus::Success,
+                    false => OutputStatus::Fail
+                }
+            }
+        } else if ticks >= self.until {
+            if !self.expectation && self.status != OutputStatus::Fail {
+                self.status = OutputStatus::Success;
+            }
+            if self.expectation && self.status != OutputStatus::Success {
+                self.status = OutputStatus::Fail;
+            }
+        }
+    }
+}
+
+impl CellType {
     pub fn is_fixed(&self) -> bool {
-        match self.cell_type.clone() {
+        match self.clone() {
             EMPTY(fixed) => fixed,
             WIRE(fixed) => fixed,
             ELECTRON(fixed) => fixed,
             TAIL(fixed) => fixed,
         }
     }
-}
 
-impl CellType {
+    /// Symbol of a cell in level files, upper case symbols are fixed cells.
+    pub fn symbol(&self) -> char {
+        match self {
+            EMPTY(false) => 'e',
+            EMPTY(true) => 'E',
+            WIRE(false) => 'w',
+            WIRE(true) => 'W',
+            ELECTRON(false) => 'a',
+            ELECTRON(true) => 'A',
+            TAIL(false) => 't',
+            TAIL(true) => 'T',
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<CellType> {
+        match symbol {
+            'e' => Some(EMPTY(false)),
+            'E' => Some(EMPTY(true)),
+            'w' => Some(WIRE(false)),
+            'W' => Some(WIRE(true)),
+            'a' => Some(ELECTRON(false)),
+            'A' => Some(ELECTRON(true)),
+            't' => Some(TAIL(false)),
+            'T' => Some(TAIL(true)),
+            _others => None,
+        }
+    }
+
     pub fn base_color(self) -> Color {
         match self {
             ELECTRON(_) => Color::YELLOW,
@@ -81,6 +167,51 @@ impl CellType {
     }
 }
 
+impl Boundary {
+    pub fn wraps_x(&self) -> bool {
+        matches!(self, Boundary::Wrap | Boundary::WrapX)
+    }
+
+    pub fn wraps_y(&self) -> bool {
+        matches!(self, Boundary::Wrap | Boundary::WrapY)
+    }
+
+    /// Mode which follows this one when the level editor cycles through modes.
+    pub fn next(&self) -> Boundary {
+        match self {
+            Boundary::Wrap => Boundary::Dead,
+            Boundary::Dead => Boundary::WrapX,
+            Boundary::WrapX => Boundary::WrapY,
+            Boundary::WrapY => Boundary::Wrap,
+        }
+    }
+}
+
+impl FromStr for Boundary {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(Boundary::Wrap),
+            "dead" => Ok(Boundary::Dead),
+            "wrap-x" => Ok(Boundary::WrapX),
+            "wrap-y" => Ok(Boundary::WrapY),
+            other => Err(Error::msg(format!("Unknown boundary mode {other}"))),
+        }
+    }
+}
+
+impl Display for Boundary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Boundary::Wrap => "wrap",
+            Boundary::Dead => "dead",
+            Boundary::WrapX => "wrap-x",
+            Boundary::WrapY => "wrap-y",
+        })
+    }
+}
+
 impl Into<String> for Point {
     fn into(self) -> String {
         format!("{} x {}", self.0, self.1)
@@ -0,0 +1,222 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Location of a problem in a level file, lines and columns start from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub excerpt: String,
+}
+
+/// Error of parsing a level file, it points to the place where parsing stopped if it's known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelError {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+/// Level file split into lines which remembers how far it has been read.
+pub struct Source<'a> {
+    lines: Vec<&'a str>,
+    next: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Field<'a> {
+    pub column: usize,
+    pub text: &'a str,
+}
+
+impl Span {
+    pub fn in_source(source: &str, line: usize, column: usize) -> Span {
+        Span {
+            line,
+            column,
+            excerpt: source.lines().nth(line.saturating_sub(1)).unwrap_or_default().to_owned(),
+        }
+    }
+}
+
+impl LevelError {
+    pub fn new(message: impl Into<String>) -> LevelError {
+        LevelError {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn at(message: impl Into<String>, span: Span) -> LevelError {
+        LevelError {
+            message: message.into(),
+            span: Some(span),
+        }
+    }
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.span {
+            Some(span) => {
+                let number = span.line.to_string();
+                let gutter = " ".repeat(number.len());
+                writeln!(f, "line {}, column {}: {}", span.line, span.column, self.message)?;
+                writeln!(f, "{number} | {}", span.excerpt)?;
+                write!(f, "{gutter} | {}^", " ".repeat(span.column.saturating_sub(1)))
+            }
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Source<'a> {
+        Source {
+            lines: text.lines().collect(),
+            next: 0,
+        }
+    }
+
+    /// Takes the next line, `expected` describes the line in the error about the end of the file.
+    pub fn next_line(&mut self, expected: &str) -> Result<Line<'a>, LevelError> {
+        match self.lines.get(self.next) {
+            Some(text) => {
+                self.next += 1;
+                Ok(Line { number: self.next, text })
+            }
+            None => {
+                let last = self.lines.last().copied().unwrap_or_default();
+                Err(LevelError::at(
+                    format!("Not found {expected}, the file ends here"),
+                    Span {
+                        line: self.lines.len().max(1),
+                        column: last.chars().count() + 1,
+                        excerpt: last.to_owned(),
+                    },
+                ))
+            }
+        }
+    }
+
+    /// Skips empty lines and takes the first line with some content.
+    pub fn next_non_empty(&mut self) -> Option<Line<'a>> {
+        while let Some(text) = self.lines.get(self.next) {
+            self.next += 1;
+            if !text.trim().is_empty() {
+                return Some(Line { number: self.next, text });
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Line<'a> {
+    /// Fields separated by spaces together with their columns.
+    pub fn fields(&self) -> Vec<Field<'a>> {
+        let mut fields = Vec::new();
+        let mut start: Option<usize> = None;
+
+        for (index, symbol) in self.text.char_indices() {
+            match (symbol == ' ', start) {
+                (false, None) => start = Some(index),
+                (true, Some(from)) => {
+                    fields.push(self.field_at(from, index));
+                    start = None;
+                }
+                _others => {}
+            }
+        }
+        if let Some(from) = start {
+            fields.push(self.field_at(from, self.text.len()));
+        }
+
+        fields
+    }
+
+    pub fn field(&self, index: usize, expected: &str) -> Result<Field<'a>, LevelError> {
+        self.fields()
+            .get(index)
+            .copied()
+            .ok_or_else(|| self.error(self.text.chars().count() + 1, format!("Not found {expected}")))
+    }
+
+    pub fn parse<T>(&self, index: usize, expected: &str) -> Result<T, LevelError>
+        where T: FromStr, T::Err: Display
+    {
+        let field = self.field(index, expected)?;
+        field.text.parse()
+            .map_err(|error| self.error(field.column, format!("Invalid {expected} '{}': {error}", field.text)))
+    }
+
+    pub fn error(&self, column: usize, message: impl Into<String>) -> LevelError {
+        LevelError::at(message, Span {
+            line: self.number,
+            column,
+            excerpt: self.text.to_owned(),
+        })
+    }
+
+    fn field_at(&self, from: usize, to: usize) -> Field<'a> {
+        Field {
+            column: self.text[..from].chars().count() + 1,
+            text: &self.text[from..to],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::resources::World;
+
+    fn error(level: &str) -> LevelError {
+        World::from_string(level.to_owned()).unwrap_err()
+    }
+
+    #[test]
+    fn fields_keep_columns() {
+        let line = Line { number: 1, text: "a  bc d" };
+        let fields: Vec<(usize, &str)> = line.fields().iter().map(|field| (field.column, field.text)).collect();
+        assert_eq!(fields, vec![(1, "a"), (4, "bc"), (7, "d")]);
+    }
+
+    #[test]
+    fn invalid_field_points_to_its_column() {
+        let error = error("3 x\nfalse\n");
+        let span = error.span.clone().unwrap();
+        assert_eq!((span.line, span.column), (1, 3));
+        assert_eq!(span.excerpt, "3 x");
+        assert!(error.message.starts_with("Invalid height 'x'"));
+        assert_eq!(error.to_string().lines().last(), Some("  |   ^"));
+    }
+
+    #[test]
+    fn missing_field_points_after_line_end() {
+        let span = error("3\nfalse\n").span.unwrap();
+        assert_eq!((span.line, span.column), (1, 2));
+    }
+
+    #[test]
+    fn unexpected_end_of_file_points_to_last_line() {
+        let error = error("2 2\nfalse\ne e\n");
+        let span = error.span.clone().unwrap();
+        assert_eq!((span.line, span.column), (3, 4));
+        assert!(error.message.contains("row of the board"));
+    }
+
+    #[test]
+    fn invalid_output_points_to_its_line() {
+        let error = error("1 1\nfalse\ne\n1\nTitle\n\n10\n0\n1\ntrue 0 10 0 y\n");
+        let span = error.span.unwrap();
+        assert_eq!((span.line, span.column), (10, 13));
+        assert_eq!(error.message, "Invalid y of output 'y': invalid digit found in string");
+    }
+}
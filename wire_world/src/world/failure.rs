@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::world::components::{Exercise, ExpectedOutput, Point};
+use crate::world::components::OutputStatus::{Fail, Success};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FailureCause {
+    /// An electron came to an output which expects silence.
+    UnexpectedElectron,
+    /// The window of an output which expects an electron is over.
+    MissedElectron,
+    /// The exercise ran longer than its timeout.
+    Timeout(usize),
+}
+
+/// Why the last exercise failed, it is shown until the player dismisses it.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub exercise: usize,
+    pub tick: usize,
+    pub cause: FailureCause,
+    /// Number of the output in the exercise, from 1 like on the analyzer panel.
+    pub output: Option<usize>,
+    /// Cell highlighted on the board.
+    pub position: Option<Point>,
+}
+
+impl Failure {
+    /// Explains a failed exercise by its outputs, they go in the order of the exercise.
+    pub fn explain(exercise: &Exercise, outputs: &[&ExpectedOutput]) -> Failure {
+        let failed = outputs.iter().enumerate()
+            .find(|(_, output)| output.status == Fail);
+        let (index, output, cause) = match failed {
+            Some((index, output)) => (Some(index), Some(output), match output.expectation {
+                true => FailureCause::MissedElectron,
+                false => FailureCause::UnexpectedElectron,
+            }),
+            None => {
+                let waiting = outputs.iter().enumerate()
+                    .find(|(_, output)| output.status != Success);
+                (waiting.map(|(index, _)| index), waiting.map(|(_, output)| output), FailureCause::Timeout(exercise.timeout))
+            }
+        };
+
+        Failure {
+            exercise: exercise.id,
+            tick: exercise.ticks,
+            cause,
+            output: index.map(|index| index + 1),
+            position: output.map(|output| output.position.clone()),
+        }
+    }
+
+    pub fn message(&self) -> String {
+        let output = match (self.output, self.position.clone()) {
+            (Some(index), Some(position)) => {
+                let position: String = position.into();
+                format!("output {index} at {position}")
+            }
+            _others => "an output".to_owned(),
+        };
+        let reason = match &self.cause {
+            FailureCause::UnexpectedElectron => format!("{output} received an unexpected electron"),
+            FailureCause::MissedElectron => format!("{output} didn't receive an expected electron"),
+            FailureCause::Timeout(timeout) if self.output.is_some() => {
+                format!("timeout of {timeout} ticks is over while {output} is waiting")
+            }
+            FailureCause::Timeout(timeout) => format!("timeout of {timeout} ticks is over"),
+        };
+
+        format!("Exercise {} failed at tick {}: {reason}", self.exercise + 1, self.tick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::OutputStatus;
+    use crate::world::components::OutputStatus::Waiting;
+
+    fn output(expectation: bool, x: usize, status: OutputStatus) -> ExpectedOutput {
+        ExpectedOutput { expectation, position: Point(x, 2), from: 5, until: 10, status }
+    }
+
+    #[test]
+    fn failed_output_is_explained() {
+        let exercise = Exercise { id: 1, ticks: 7, timeout: 20 };
+        let outputs = [output(true, 3, Success), output(false, 4, Fail)];
+        let failure = Failure::explain(&exercise, &outputs.iter().collect::<Vec<_>>());
+
+        assert_eq!(failure.cause, FailureCause::UnexpectedElectron);
+        assert_eq!(failure.position, Some(Point(4, 2)));
+        assert_eq!(failure.message(), "Exercise 2 failed at tick 7: output 2 at 4 x 2 received an unexpected electron");
+
+        let outputs = [output(true, 3, Fail)];
+        let failure = Failure::explain(&exercise, &outputs.iter().collect::<Vec<_>>());
+        assert_eq!(failure.cause, FailureCause::MissedElectron);
+        assert_eq!(failure.output, Some(1));
+    }
+
+    #[test]
+    fn timeout_points_to_waiting_output() {
+        let exercise = Exercise { id: 0, ticks: 21, timeout: 20 };
+        let outputs = [output(true, 3, Success), output(true, 6, Waiting)];
+        let failure = Failure::explain(&exercise, &outputs.iter().collect::<Vec<_>>());
+
+        assert_eq!(failure.cause, FailureCause::Timeout(20));
+        assert_eq!(failure.position, Some(Point(6, 2)));
+        assert_eq!(failure.message(), "Exercise 1 failed at tick 21: timeout of 20 ticks is over while output 2 at 6 x 2 is waiting");
+    }
+}
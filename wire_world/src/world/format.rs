@@ -0,0 +1,324 @@
+use std::path::Path;
+
+use anyhow::Error;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::world::components::{Boundary, CellType, Point};
+use crate::world::diagnostics::{LevelError, Span};
+use crate::world::resources::{ExerciseData, World};
+
+/// Version written by `World::to_ron`. Files with a greater version are rejected.
+pub const LEVEL_FORMAT_VERSION: u32 = 1;
+
+/// Level file in the self-describing RON format, usually named `*.level.ron`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LevelFile {
+    pub version: u32,
+    pub title: String,
+    pub width: usize,
+    pub height: usize,
+    #[serde(default)]
+    pub boundary: Boundary,
+    pub electron_available: bool,
+    /// Rows of the board, one symbol per cell: `e`, `w`, `a`, `t` and their upper case fixed variants.
+    pub grid: Vec<String>,
+    #[serde(default)]
+    pub exercises: Vec<ExerciseFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExerciseFile {
+    pub description: String,
+    pub timeout: usize,
+    #[serde(default)]
+    pub spawns: Vec<SpawnFile>,
+    #[serde(default)]
+    pub outputs: Vec<OutputFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpawnFile {
+    pub x: usize,
+    pub y: usize,
+    pub instant: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutputFile {
+    pub x: usize,
+    pub y: usize,
+    pub expectation: bool,
+    pub from: usize,
+    pub until: usize,
+}
+
+/// Kind of level file, chosen by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelFormat {
+    Legacy,
+    Ron,
+}
+
+impl LevelFormat {
+    pub fn from_path(path: &Path) -> LevelFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ron") => LevelFormat::Ron,
+            _others => LevelFormat::Legacy,
+        }
+    }
+}
+
+impl World {
+    /// Parses a level in the format matching the file name, a missing title is taken from the file name.
+    pub fn from_file(path: &Path, content: String) -> Result<World, LevelError> {
+        let mut world = match LevelFormat::from_path(path) {
+            LevelFormat::Legacy => World::from_string(content)?,
+            LevelFormat::Ron => World::from_ron(&content)?,
+        };
+        if world.title.is_empty() {
+            world.title = title_from_path(path);
+        }
+
+        Ok(world)
+    }
+
+    pub fn from_ron(s: &str) -> Result<World, LevelError> {
+        let file: LevelFile = ron::from_str(s)
+            .map_err(|error| LevelError::at(
+                error.code.to_string(),
+                Span::in_source(s, error.position.line, error.position.col),
+            ))?;
+        if file.version > LEVEL_FORMAT_VERSION {
+            return Err(located(
+                s,
+                locate_key(s, "version"),
+                format!("Level version {} is newer than supported version {LEVEL_FORMAT_VERSION}", file.version),
+            ));
+        }
+        if file.grid.len() != file.height {
+            return Err(located(
+                s,
+                locate_key(s, "grid"),
+                format!("Grid has {} rows but height is {}", file.grid.len(), file.height),
+            ));
+        }
+
+        let mut map: Vec<CellType> = Vec::with_capacity(file.width * file.height);
+        for (y, row) in file.grid.iter().enumerate() {
+            let row: Vec<char> = row.chars().collect();
+            let position = locate_row(s, y);
+            if row.len() != file.width {
+                return Err(located(
+                    s,
+                    position,
+                    format!("Row {y} has {} cells but width is {}", row.len(), file.width),
+                ));
+            }
+            for (x, symbol) in row.into_iter().enumerate() {
+                let cell = CellType::from_symbol(symbol).ok_or_else(|| located(
+                    s,
+                    position.map(|(line, column)| (line, column + 1 + x)),
+                    format!("Unknown cell '{symbol}' at {x} x {y}"),
+                ))?;
+                map.push(cell);
+            }
+        }
+
+        let exercises = file.exercises.into_iter()
+            .map(|exercise| ExerciseData {
+                description: exercise.description,
+                timeout: exercise.timeout,
+                spawns: exercise.spawns.into_iter()
+                    .map(|spawn| (Point(spawn.x, spawn.y), spawn.instant))
+                    .collect(),
+                outputs: exercise.outputs.into_iter()
+                    .map(|output| (Point(output.x, output.y), output.expectation, output.from, output.until))
+                    .collect(),
+            })
+            .collect();
+
+        Ok(
+            World {
+                title: file.title,
+                size: (file.width, file.height),
+                boundary: file.boundary,
+                map,
+                electron_available: file.electron_available,
+                exercises,
+            }
+        )
+    }
+
+    pub fn to_ron(&self) -> anyhow::Result<String, Error> {
+        let file = LevelFile {
+            version: LEVEL_FORMAT_VERSION,
+            title: self.title.clone(),
+            width: self.size.0,
+            height: self.size.1,
+            boundary: self.boundary,
+            electron_available: self.electron_available,
+            grid: self.map.chunks(self.size.0)
+                .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+                .collect(),
+            exercises: self.exercises.iter()
+                .map(|exercise| ExerciseFile {
+                    description: exercise.description.clone(),
+                    timeout: exercise.timeout,
+                    spawns: exercise.spawns.iter()
+                        .map(|(point, instant)| SpawnFile { x: point.0, y: point.1, instant: *instant })
+                        .collect(),
+                    outputs: exercise.outputs.iter()
+                        .map(|(point, expectation, from, until)| OutputFile {
+                            x: point.0,
+                            y: point.1,
+                            expectation: *expectation,
+                            from: *from,
+                            until: *until,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+
+        Ok(ron::ser::to_string_pretty(&file, PrettyConfig::new().depth_limit(4))?)
+    }
+}
+
+fn located(source: &str, position: Option<(usize, usize)>, message: String) -> LevelError {
+    match position {
+        Some((line, column)) => LevelError::at(message, Span::in_source(source, line, column)),
+        None => LevelError::new(message),
+    }
+}
+
+/// Line and column of a field name in a RON level.
+fn locate_key(source: &str, key: &str) -> Option<(usize, usize)> {
+    source.lines()
+        .enumerate()
+        .find_map(|(index, line)| {
+            let column = line.find(&format!("{key}:"))?;
+            Some((index + 1, line[..column].chars().count() + 1))
+        })
+}
+
+/// Line and column of the opening quote of a grid row, rows are expected on separate lines.
+fn locate_row(source: &str, row: usize) -> Option<(usize, usize)> {
+    let (grid_line, _) = locate_key(source, "grid")?;
+    source.lines()
+        .enumerate()
+        .skip(grid_line)
+        .filter_map(|(index, line)| {
+            let column = line.find('"')?;
+            Some((index + 1, line[..column].chars().count() + 1))
+        })
+        .nth(row)
+}
+
+/// Levels without exercises have no description to take a title from, so the file name is used.
+pub fn title_from_path(path: &Path) -> String {
+    let stem = path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default()
+        .replace('_', " ");
+    let mut chars = stem.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => stem,
+    }
+}
+
+/// Name of the converted file: `and.level` becomes `and.level.ron`.
+pub fn converted_path(path: &Path) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".ron");
+    name.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::CellType::{ELECTRON, EMPTY, WIRE};
+
+    const DIODE: &str = include_str!("../../assets/diode.level");
+
+    #[test]
+    fn legacy_level_survives_conversion() {
+        let legacy = World::from_file(Path::new("diode.level"), DIODE.to_owned()).unwrap();
+        let converted = World::from_ron(&legacy.to_ron().unwrap()).unwrap();
+
+        assert_eq!(converted.title, "Diode");
+        assert_eq!(converted.size, legacy.size);
+        assert_eq!(converted.boundary, legacy.boundary);
+        assert_eq!(converted.map, legacy.map);
+        assert_eq!(converted.electron_available, legacy.electron_available);
+        assert_eq!(converted.exercises.len(), legacy.exercises.len());
+        for (converted, legacy) in converted.exercises.iter().zip(legacy.exercises.iter()) {
+            assert_eq!(converted.description, legacy.description);
+            assert_eq!(converted.timeout, legacy.timeout);
+            assert_eq!(converted.spawns, legacy.spawns);
+            assert_eq!(converted.outputs, legacy.outputs);
+        }
+    }
+
+    #[test]
+    fn parse_ron_level() {
+        let world = World::from_ron(r#"(
+            version: 1,
+            title: "Wire",
+            width: 3,
+            height: 2,
+            boundary: Dead,
+            electron_available: true,
+            grid: [
+                "Awe",
+                "eeE",
+            ],
+            exercises: [
+                (
+                    description: "Deliver signal",
+                    timeout: 10,
+                    spawns: [(x: 0, y: 0, instant: 1)],
+                    outputs: [(x: 1, y: 0, expectation: true, from: 0, until: 10)],
+                ),
+            ],
+        )"#).unwrap();
+
+        assert_eq!(world.boundary, Boundary::Dead);
+        assert_eq!(world.map, vec![ELECTRON(true), WIRE(false), EMPTY(false), EMPTY(false), EMPTY(false), EMPTY(true)]);
+        assert_eq!(world.exercises[0].spawns, vec![(Point(0, 0), 1)]);
+        assert_eq!(world.exercises[0].outputs, vec![(Point(1, 0), true, 0, 10)]);
+    }
+
+    #[test]
+    fn ron_errors_point_to_their_lines() {
+        let level = "(\n    version: 1,\n    title: \"\",\n    width: 2,\n    height: 2,\n    electron_available: false,\n    grid: [\n        \"ee\",\n        \"eq\",\n    ],\n)";
+        let span = World::from_ron(level).unwrap_err().span.unwrap();
+        assert_eq!((span.line, span.column, span.excerpt.as_str()), (9, 11, "        \"eq\","));
+
+        let broken = level.replace("width: 2", "width 2");
+        let span = World::from_ron(&broken).unwrap_err().span.unwrap();
+        assert_eq!(span.line, 4);
+    }
+
+    #[test]
+    fn reject_short_row_and_newer_version() {
+        let level = |version: u32, row: &str| format!(
+            "(version: {version}, title: \"\", width: 3, height: 1, electron_available: false, grid: [\"{row}\"])"
+        );
+
+        assert!(World::from_ron(&level(1, "eee")).is_ok());
+        assert!(World::from_ron(&level(1, "ee")).is_err());
+        assert!(World::from_ron(&level(2, "eee")).is_err());
+    }
+
+    #[test]
+    fn choose_format_by_file_name() {
+        assert_eq!(LevelFormat::from_path(Path::new("assets/and.level")), LevelFormat::Legacy);
+        assert_eq!(LevelFormat::from_path(Path::new("assets/and.level.ron")), LevelFormat::Ron);
+        assert_eq!(converted_path(Path::new("assets/and.level")), Path::new("assets/and.level.ron"));
+        assert_eq!(title_from_path(Path::new("assets/forward_blocker.level.ron")), "Forward blocker");
+    }
+}
@@ -0,0 +1,95 @@
+use crate::world::components::CellType;
+use crate::world::components::CellType::{ELECTRON, EMPTY, TAIL, WIRE};
+
+pub const STATE_MASK: u8 = 0b011;
+pub const FIXED: u8 = 0b100;
+
+pub const EMPTY_STATE: u8 = 0;
+pub const WIRE_STATE: u8 = 1;
+pub const ELECTRON_STATE: u8 = 2;
+pub const TAIL_STATE: u8 = 3;
+
+/// Dense board storage, one byte per cell.
+/// The two lowest bits keep the state of a cell and the third one keeps the fixed flag.
+/// Next generation is written into the back buffer, so readers always see a complete generation.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    size: (usize, usize),
+    front: Vec<u8>,
+    back: Vec<u8>,
+}
+
+impl Grid {
+    pub fn new(size: (usize, usize), cells: &[CellType]) -> Grid {
+        let front: Vec<u8> = cells.iter().map(encode).collect();
+
+        Grid {
+            size,
+            back: front.clone(),
+            front,
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        self.front[index]
+    }
+
+    pub fn get_cell(&self, index: usize) -> CellType {
+        decode(self.front[index])
+    }
+
+    /// Writes a cell into both buffers.
+    pub fn set(&mut self, index: usize, cell: u8) {
+        self.front[index] = cell;
+        self.back[index] = cell;
+    }
+
+    pub fn set_next(&mut self, index: usize, cell: u8) {
+        self.back[index] = cell;
+    }
+
+    /// Makes the next generation current. Only `touched` cells may differ between buffers,
+    /// so only they are copied back to keep both buffers in sync.
+    pub fn swap(&mut self, touched: &[u32]) {
+        std::mem::swap(&mut self.front, &mut self.back);
+        for index in touched {
+            let index = *index as usize;
+            self.back[index] = self.front[index];
+        }
+    }
+}
+
+pub fn encode(cell: &CellType) -> u8 {
+    match cell {
+        EMPTY(fixed) => EMPTY_STATE | fixed_bit(*fixed),
+        WIRE(fixed) => WIRE_STATE | fixed_bit(*fixed),
+        ELECTRON(fixed) => ELECTRON_STATE | fixed_bit(*fixed),
+        TAIL(fixed) => TAIL_STATE | fixed_bit(*fixed),
+    }
+}
+
+pub fn decode(cell: u8) -> CellType {
+    let fixed = cell & FIXED != 0;
+    match cell & STATE_MASK {
+        WIRE_STATE => WIRE(fixed),
+        ELECTRON_STATE => ELECTRON(fixed),
+        TAIL_STATE => TAIL(fixed),
+        _others => EMPTY(fixed),
+    }
+}
+
+fn fixed_bit(fixed: bool) -> u8 {
+    if fixed { FIXED } else { 0 }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::world::components::{CellType, Point};
+
+/// Count of steps which can be undone, older steps are forgotten.
+pub const HISTORY_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellEdit {
+    pub point: Point,
+    pub before: CellType,
+    pub after: CellType,
+}
+
+/// Undo and redo stacks of player edits. One step is everything changed by one action,
+/// for example a whole painted stroke or a paste.
+#[derive(Resource, Debug, Default)]
+pub struct EditHistory {
+    undo: Vec<Vec<CellEdit>>,
+    redo: Vec<Vec<CellEdit>>,
+}
+
+impl EditHistory {
+    /// Remembers a new step, steps which were undone can't be redone after it.
+    pub fn record(&mut self, step: Vec<CellEdit>) {
+        if step.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+        self.undo.push(step);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Cells to set to revert the last step.
+    pub fn undo(&mut self) -> Option<Vec<(Point, CellType)>> {
+        let step = self.undo.pop()?;
+        let cells = step.iter()
+            .rev()
+            .map(|edit| (edit.point.clone(), edit.before.clone()))
+            .collect();
+        self.redo.push(step);
+
+        Some(cells)
+    }
+
+    /// Cells to set to repeat the last undone step.
+    pub fn redo(&mut self) -> Option<Vec<(Point, CellType)>> {
+        let step = self.redo.pop()?;
+        let cells = step.iter()
+            .map(|edit| (edit.point.clone(), edit.after.clone()))
+            .collect();
+        self.undo.push(step);
+
+        Some(cells)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// One step of an edit: the last state of a cell listed twice wins and cells which don't change are dropped.
+/// `current` gives the state of a cell before the edit.
+pub fn cell_edits(cells: &[(Point, CellType)], current: impl Fn(&Point) -> CellType) -> Vec<CellEdit> {
+    let mut step: Vec<CellEdit> = Vec::with_capacity(cells.len());
+    let mut index: HashMap<&Point, usize> = HashMap::with_capacity(cells.len());
+    for (point, cell_type) in cells.iter() {
+        match index.get(point) {
+            Some(&position) => step[position].after = cell_type.clone(),
+            None => {
+                index.insert(point, step.len());
+                step.push(CellEdit {
+                    point: point.clone(),
+                    before: current(point),
+                    after: cell_type.clone(),
+                });
+            }
+        }
+    }
+    step.retain(|edit| edit.before != edit.after);
+
+    step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::CellType::{EMPTY, WIRE};
+
+    fn wire(x: usize) -> CellEdit {
+        CellEdit {
+            point: Point(x, 0),
+            before: EMPTY(false),
+            after: WIRE(false),
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_whole_steps() {
+        let mut history = EditHistory::default();
+        history.record(vec![wire(0), wire(1)]);
+        history.record(vec![wire(2)]);
+
+        assert_eq!(history.undo(), Some(vec![(Point(2, 0), EMPTY(false))]));
+        assert_eq!(history.undo(), Some(vec![(Point(1, 0), EMPTY(false)), (Point(0, 0), EMPTY(false))]));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(vec![(Point(0, 0), WIRE(false)), (Point(1, 0), WIRE(false))]));
+        assert!(history.can_undo() && history.can_redo());
+    }
+
+    #[test]
+    fn new_step_drops_redo() {
+        let mut history = EditHistory::default();
+        history.record(vec![wire(0)]);
+        history.undo();
+        history.record(vec![]);
+        assert!(history.can_redo());
+
+        history.record(vec![wire(1)]);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn history_is_limited() {
+        let mut history = EditHistory::default();
+        for x in 0..HISTORY_LIMIT + 10 {
+            history.record(vec![wire(x)]);
+        }
+
+        let mut count = 0;
+        while history.undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn edit_keeps_last_state_of_each_cell() {
+        let cells = vec![
+            (Point(0, 0), WIRE(false)),
+            (Point(1, 0), WIRE(false)),
+            (Point(0, 0), EMPTY(false)),
+            (Point(2, 0), WIRE(false)),
+            (Point(1, 0), EMPTY(false)),
+            (Point(1, 0), WIRE(false)),
+        ];
+        let step = cell_edits(&cells, |point| match point.0 {
+            2 => WIRE(false),
+            _others => EMPTY(false),
+        });
+
+        assert_eq!(step, vec![wire(1)]);
+    }
+}
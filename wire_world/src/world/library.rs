@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::world::clipboard::Pattern;
+use crate::world::components::CellType;
+use crate::world::diagnostics::{LevelError, Span};
+
+/// Version written by `PatternLibrary::user_to_ron`. Files with a greater version are rejected.
+pub const PATTERNS_FORMAT_VERSION: u32 = 1;
+
+/// Name of the file with patterns saved by the player, it is kept next to the assets directory.
+pub const USER_PATTERNS_FILE: &str = "patterns.user.ron";
+
+/// Symbol of a pattern cell which keeps the board under it, fixed cells of a copied region become holes.
+pub const HOLE: char = '.';
+
+/// Built into the binary, so the browser build has the library without loading assets.
+const BUILTIN_PATTERNS: &str = include_str!("../../assets/patterns.ron");
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternsFile {
+    pub version: u32,
+    pub patterns: Vec<PatternFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternFile {
+    pub name: String,
+    /// Rows of the pattern with the level cell symbols, only free cells and holes are allowed.
+    pub grid: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedPattern {
+    pub name: String,
+    pub pattern: Pattern,
+}
+
+/// Built-in patterns followed by patterns saved by the player.
+#[derive(Resource, Debug)]
+pub struct PatternLibrary {
+    pub patterns: Vec<NamedPattern>,
+    builtin: usize,
+    pub status: String,
+}
+
+impl Default for PatternLibrary {
+    fn default() -> Self {
+        let patterns = parse_patterns(BUILTIN_PATTERNS).expect("Built-in patterns are valid");
+        PatternLibrary {
+            builtin: patterns.len(),
+            patterns,
+            status: String::new(),
+        }
+    }
+}
+
+impl PatternLibrary {
+    pub fn user_patterns(&self) -> &[NamedPattern] {
+        &self.patterns[self.builtin..]
+    }
+
+    /// Adds a pattern saved by the player under the next free name and returns the name.
+    pub fn add(&mut self, pattern: Pattern) -> String {
+        let name = (self.user_patterns().len() + 1..)
+            .map(|index| format!("Custom {index}"))
+            .find(|name| self.patterns.iter().all(|named| named.name != *name))
+            .unwrap_or_default();
+        self.patterns.push(NamedPattern { name: name.clone(), pattern });
+
+        name
+    }
+
+    pub fn user_to_ron(&self) -> anyhow::Result<String> {
+        let file = PatternsFile {
+            version: PATTERNS_FORMAT_VERSION,
+            patterns: self.user_patterns().iter()
+                .map(|named| PatternFile {
+                    name: named.name.clone(),
+                    grid: named.pattern.to_rows(),
+                })
+                .collect(),
+        };
+
+        Ok(ron::ser::to_string_pretty(&file, PrettyConfig::new().depth_limit(3))?)
+    }
+}
+
+pub fn parse_patterns(s: &str) -> Result<Vec<NamedPattern>, LevelError> {
+    let file: PatternsFile = ron::from_str(s)
+        .map_err(|error| LevelError::at(
+            error.code.to_string(),
+            Span::in_source(s, error.position.line, error.position.col),
+        ))?;
+    if file.version > PATTERNS_FORMAT_VERSION {
+        return Err(LevelError::new(format!(
+            "Patterns version {} is newer than supported version {PATTERNS_FORMAT_VERSION}",
+            file.version,
+        )));
+    }
+
+    file.patterns.into_iter()
+        .map(|file| {
+            let pattern = Pattern::from_rows(&file.grid)
+                .map_err(|message| LevelError::new(format!("Pattern {}: {message}", file.name)))?;
+            Ok(NamedPattern { name: file.name, pattern })
+        })
+        .collect()
+}
+
+impl Pattern {
+    pub fn from_rows(rows: &[String]) -> Result<Pattern, String> {
+        let width = rows.first().map(|row| row.chars().count()).unwrap_or_default();
+        if width == 0 {
+            return Err("Pattern is empty".to_owned());
+        }
+
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!("Row {y} has {} cells but width is {width}", row.chars().count()));
+            }
+            for (x, symbol) in row.chars().enumerate() {
+                cells.push(match (symbol, CellType::from_symbol(symbol)) {
+                    (HOLE, _) => None,
+                    (_, Some(cell_type)) if !cell_type.is_fixed() => Some(cell_type),
+                    _others => return Err(format!("Unexpected cell '{symbol}' at {x} x {y}")),
+                });
+            }
+        }
+
+        Ok(Pattern { size: (width, rows.len()), cells })
+    }
+
+    pub fn to_rows(&self) -> Vec<String> {
+        self.cells.chunks(self.size.0)
+            .map(|row| row.iter()
+                .map(|cell| cell.as_ref().map(|cell| cell.symbol()).unwrap_or(HOLE))
+                .collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::Point;
+    use crate::world::resources::World;
+    use crate::world::simulation::Simulation;
+
+    fn pattern(library: &PatternLibrary, name: &str) -> Pattern {
+        library.patterns.iter().find(|named| named.name == name).unwrap().pattern.clone()
+    }
+
+    /// Stamps the pattern onto an empty board with dead edges, starts electrons at the inputs
+    /// and returns ticks at which electrons reach the output.
+    fn signals(pattern: &Pattern, inputs: &[Point], output: &Point) -> Vec<usize> {
+        let (width, height) = pattern.size;
+        let level = format!("{width} {height} dead\ntrue\n{}\n0\n", vec![vec!["e"; width].join(" "); height].join("\n"));
+        let mut simulation = Simulation::new(&World::from_string(level).unwrap());
+        for (point, cell_type) in pattern.place(&Point(0, 0), &simulation, true) {
+            simulation.set_cell(&point, cell_type);
+        }
+        for input in inputs {
+            simulation.set_cell(input, CellType::ELECTRON(false));
+        }
+
+        let mut ticks = Vec::new();
+        for tick in 0..60 {
+            if let CellType::ELECTRON(_) = simulation.get_cell(output) {
+                ticks.push(tick);
+            }
+            simulation.step();
+        }
+        ticks
+    }
+
+    #[test]
+    fn builtin_patterns_are_valid() {
+        let library = PatternLibrary::default();
+        assert!(library.patterns.len() >= 9);
+        assert!(library.user_patterns().is_empty());
+    }
+
+    #[test]
+    fn diode_passes_one_way() {
+        let diode = pattern(&PatternLibrary::default(), "Diode");
+        assert_eq!(signals(&diode, &[Point(0, 1)], &Point(5, 1)), vec![5]);
+        assert!(signals(&diode, &[Point(5, 1)], &Point(0, 1)).is_empty());
+    }
+
+    #[test]
+    fn gates_follow_truth_tables() {
+        let library = PatternLibrary::default();
+        let xor = pattern(&library, "XOR");
+        let out = Point(6, 3);
+        assert!(!signals(&xor, &[Point(0, 0)], &out).is_empty());
+        assert!(!signals(&xor, &[Point(0, 6)], &out).is_empty());
+        assert!(signals(&xor, &[Point(0, 0), Point(0, 6)], &out).is_empty());
+
+        let and_not = pattern(&library, "AND-NOT");
+        let out = Point(23, 6);
+        assert!(!signals(&and_not, &[Point(0, 1)], &out).is_empty());
+        assert!(signals(&and_not, &[Point(0, 9)], &out).is_empty());
+        assert!(signals(&and_not, &[Point(0, 1), Point(0, 9)], &out).is_empty());
+    }
+
+    #[test]
+    fn crossover_swaps_signals() {
+        let crossover = pattern(&PatternLibrary::default(), "Crossover");
+        let (top, bottom) = (Point(30, 3), Point(30, 17));
+        assert!(signals(&crossover, &[Point(0, 0)], &top).is_empty());
+        assert!(!signals(&crossover, &[Point(0, 0)], &bottom).is_empty());
+        assert!(!signals(&crossover, &[Point(0, 20)], &top).is_empty());
+        assert!(signals(&crossover, &[Point(0, 20)], &bottom).is_empty());
+    }
+
+    #[test]
+    fn clock_has_its_period() {
+        let clock = pattern(&PatternLibrary::default(), "Clock 6");
+        let ticks = signals(&clock, &[], &Point(5, 1));
+        assert!(ticks.windows(2).all(|pair| pair[1] - pair[0] == 6));
+        assert!(ticks.len() > 3);
+    }
+
+    #[test]
+    fn user_patterns_round_trip() {
+        let mut library = PatternLibrary::default();
+        let pattern = Pattern::from_rows(&["w.".to_owned(), "ea".to_owned()]).unwrap();
+        assert_eq!(library.add(pattern.clone()), "Custom 1");
+        assert_eq!(library.add(pattern.clone()), "Custom 2");
+
+        let saved = parse_patterns(&library.user_to_ron().unwrap()).unwrap();
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved[1], NamedPattern { name: "Custom 2".to_owned(), pattern });
+        assert!(Pattern::from_rows(&["wW".to_owned()]).is_err());
+        assert!(Pattern::from_rows(&["ww".to_owned(), "w".to_owned()]).is_err());
+    }
+}
@@ -2,16 +2,43 @@ use std::time::Duration;
 use bevy::app::{App, Plugin};
 use bevy::prelude::*;
 use crate::{GameState, LevelState};
-use crate::world::components::ChangeExercise;
-use crate::world::resources::{Counter, LevelConfig, World};
+use crate::world::components::{Change, ChangeExercise, Edit, OpenLevel};
+use crate::world::resources::{Counter, LevelConfig, LevelLoadErrors, LevelManifest, LevelsCatalog, World};
+use crate::world::history::EditHistory;
 use crate::world::services::*;
-use crate::world::world_loader::WorldLoader;
+use crate::world::library::PatternLibrary;
+use crate::world::probes::Probes;
+use crate::world::progress::Progress;
+use crate::world::recording::Recording;
+use crate::world::score::Scoring;
+use crate::world::stepper::Stepper;
+use crate::world::tools::Toolbox;
+use crate::world::world_loader::{ManifestLoader, WorldLoader};
 
 pub mod components;
 pub mod resources;
 pub mod services;
 pub mod world_loader;
 pub mod tweens;
+pub mod simulation;
+pub mod grid;
+pub mod board;
+pub mod format;
+pub mod diagnostics;
+pub mod validation;
+pub mod verifier;
+pub mod history;
+pub mod tools;
+pub mod clipboard;
+pub mod library;
+pub mod stepper;
+pub mod recording;
+pub mod probes;
+pub mod failure;
+pub mod progress;
+pub mod storage;
+pub mod score;
+pub mod packs;
 
 pub const CELL_SIZE: f32 = 40.;
 
@@ -19,27 +46,56 @@ pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
+        let errors = LevelLoadErrors::default();
         app.add_asset::<World>()
-            .add_asset_loader(WorldLoader)
+            .add_asset::<LevelManifest>()
+            .insert_resource(errors.clone())
+            .add_asset_loader(WorldLoader { errors })
+            .add_asset_loader(ManifestLoader)
+            .init_resource::<LevelsCatalog>()
+            .init_resource::<EditHistory>()
+            .init_resource::<Toolbox>()
+            .init_resource::<PatternLibrary>()
+            .init_resource::<Stepper>()
+            .init_resource::<Recording>()
+            .init_resource::<Probes>()
+            .init_resource::<Progress>()
+            .init_resource::<Scoring>()
             .insert_resource(Counter {
                 timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating)
             })
             .insert_resource(LevelConfig::empty())
             .add_event::<ChangeExercise>()
+            .add_event::<Change>()
+            .add_event::<Edit>()
+            .add_event::<OpenLevel>()
+            .add_systems(Startup, (load_levels_manifest, load_progress))
+            .add_systems(Update, (load_levels_catalog, save_progress))
             .add_systems(OnEnter(GameState::Level), init_level)
-            .add_systems(OnExit(GameState::Level), destroy_level)
+            .add_systems(OnExit(GameState::Level), (destroy_level, flush_progress))
+            .add_systems(OnEnter(LevelState::Finish), score_level)
             .add_systems(Update, (
                 load_level,
-                handle_clicks,
+                handle_clicks.before(apply_edits),
+                handle_strokes.before(apply_edits),
+                draw_tool_preview.after(handle_strokes).after(handle_clicks),
+                draw_probe_marks.after(handle_clicks),
+                draw_failure_mark.after(handle_exercises),
+                keyboard_cursor.before(handle_clicks),
+                draw_keyboard_cursor.after(keyboard_cursor),
                 handle_outputs.after(change_exercise),
                 handle_exercises.after(handle_outputs),
                 outputs_indication,
                 change_exercise,
             ).run_if(in_state(GameState::Level).and_then(in_state(LevelState::Process))))
             .add_systems(Update, (
-                find_cell_to_update,
-                update_cells,
-                spawn_electron,
+                apply_edits.before(apply_changes),
+                apply_changes.before(tick_simulation),
+                replay_recording.after(apply_changes).before(tick_simulation),
+                keep_player_board.after(apply_changes),
+                open_level.after(keep_player_board),
+                tick_simulation,
+                spawn_electron.after(tick_simulation),
             ).run_if(in_state(GameState::Level)))
             ;
     }
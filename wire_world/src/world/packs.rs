@@ -0,0 +1,198 @@
+use crate::world::progress::Progress;
+
+/// Levels of the manifest under one `[Name]` header. A pack is opened when every level of the
+/// packs it requires is solved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelPack {
+    pub name: String,
+    pub requires: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelEntry {
+    pub file_name: String,
+    /// Index of the pack, levels above the first header belong to no pack.
+    pub pack: Option<usize>,
+    /// Levels which have to be solved before this one is opened.
+    pub requires: Vec<String>,
+}
+
+/// Packs and prerequisites of the levels manifest, in the order of the manifest.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelPacks {
+    pub packs: Vec<LevelPack>,
+    pub entries: Vec<LevelEntry>,
+}
+
+/// Names listed after the `after` keyword, separated by commas.
+fn requirements(rest: &str) -> Vec<String> {
+    match rest.trim().strip_prefix("after") {
+        Some(names) => names.split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_owned())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+impl LevelPacks {
+    /// Reads `[Pack] after Other, Another` headers and `level after other.level` lines,
+    /// plain lines of level files are levels without prerequisites.
+    pub fn parse(s: &str) -> LevelPacks {
+        let mut packs = LevelPacks::default();
+        for line in s.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, rest)) = line.strip_prefix('[').and_then(|line| line.split_once(']')) {
+                packs.packs.push(LevelPack { name: name.trim().to_owned(), requires: requirements(rest) });
+                continue;
+            }
+
+            let (file_name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            packs.entries.push(LevelEntry {
+                file_name: file_name.to_owned(),
+                pack: packs.packs.len().checked_sub(1),
+                requires: requirements(rest),
+            });
+        }
+
+        packs
+    }
+
+    pub fn levels(&self) -> Vec<String> {
+        self.entries.iter().map(|entry| entry.file_name.clone()).collect()
+    }
+
+    pub fn pack(&self, level: &str) -> Option<&LevelPack> {
+        self.entry(level)
+            .and_then(|entry| entry.pack)
+            .and_then(|pack| self.packs.get(pack))
+    }
+
+    fn entry(&self, level: &str) -> Option<&LevelEntry> {
+        self.entries.iter().find(|entry| entry.file_name == level)
+    }
+
+    /// Levels which have to be solved before the level is opened. Names which aren't in the manifest
+    /// are skipped, otherwise a typo would lock a level forever.
+    pub fn prerequisites(&self, level: &str) -> Vec<&str> {
+        let Some(entry) = self.entry(level) else {
+            return Vec::new();
+        };
+        let required_packs: Vec<usize> = self.pack(level)
+            .map(|pack| pack.requires.iter()
+                .filter_map(|name| self.packs.iter().position(|pack| &pack.name == name))
+                .collect())
+            .unwrap_or_default();
+
+        self.entries.iter()
+            .filter(|other| entry.requires.contains(&other.file_name)
+                || other.pack.map(|pack| required_packs.contains(&pack)).unwrap_or_default())
+            .map(|other| other.file_name.as_str())
+            .filter(|other| *other != level)
+            .collect()
+    }
+
+    pub fn is_unlocked(&self, level: &str, progress: &Progress) -> bool {
+        self.prerequisites(level).iter().all(|required| progress.is_solved(required))
+    }
+
+    /// First unlocked level after the given one in the order of the manifest.
+    pub fn next_level(&self, level: &str, progress: &Progress) -> Option<&str> {
+        let index = self.entries.iter().position(|entry| entry.file_name == level)?;
+        self.entries[index + 1..].iter()
+            .map(|entry| entry.file_name.as_str())
+            .find(|next| self.is_unlocked(next, progress))
+    }
+}
+
+/// Puts the new level file in place of the old one in a manifest line, both as the level of the line and
+/// among the names after `after`, so levels and packs which required the old file keep requiring it.
+/// Lines which don't mention the old file give `None`.
+pub fn rename_level(line: &str, old: &str, new: &str) -> Option<String> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    let (head, rest, renamed) = match trimmed.strip_prefix('[').and_then(|line| line.split_once(']')) {
+        Some((name, rest)) => (format!("[{}]", name.trim()), rest.trim(), false),
+        None => {
+            let (file_name, rest) = trimmed.split_once(char::is_whitespace).unwrap_or((trimmed, ""));
+            match file_name == old {
+                true => (new.to_owned(), rest.trim(), true),
+                false => (file_name.to_owned(), rest.trim(), false),
+            }
+        }
+    };
+    let requires = requirements(rest);
+    if !renamed && !requires.iter().any(|name| name == old) {
+        return None;
+    }
+
+    let requires: Vec<String> = requires.into_iter()
+        .map(|name| if name == old { new.to_owned() } else { name })
+        .collect();
+    Some(match (requires.is_empty(), rest.is_empty()) {
+        (false, _) => format!("{head} after {}", requires.join(", ")),
+        (true, true) => head,
+        (true, false) => format!("{head} {rest}"),
+    })
+}
+
+/// File name of the level on a manifest line, headers and comments have none.
+pub fn level_file_name(line: &str) -> Option<&str> {
+    let line = line.trim();
+    match line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+        true => None,
+        false => line.split_whitespace().next(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "# levels\nfree.level\n[Signals]\ncrossroad.level\nforward.level\n\
+        [Gates] after Signals\ndiode.level\nblocker.level after diode.level\nor.level after blocker.level, diode.level\n";
+
+    #[test]
+    fn manifest_packs_are_parsed() {
+        let packs = LevelPacks::parse(MANIFEST);
+
+        assert_eq!(packs.levels(), vec!["free.level", "crossroad.level", "forward.level", "diode.level", "blocker.level", "or.level"]);
+        assert_eq!(packs.pack("free.level"), None);
+        assert_eq!(packs.pack("diode.level").unwrap(), &LevelPack { name: "Gates".to_owned(), requires: vec!["Signals".to_owned()] });
+        assert_eq!(packs.prerequisites("crossroad.level"), Vec::<&str>::new());
+        assert_eq!(packs.prerequisites("or.level"), vec!["crossroad.level", "forward.level", "diode.level", "blocker.level"]);
+        assert_eq!(rename_level(" blocker.level after diode.level", "blocker.level", "blocker.level.ron"),
+                   Some("blocker.level.ron after diode.level".to_owned()));
+        assert_eq!(rename_level("or.level after blocker.level,  diode.level", "diode.level", "diode.level.ron"),
+                   Some("or.level after blocker.level, diode.level.ron".to_owned()));
+        assert_eq!(rename_level("[Gates] after Signals, diode.level", "diode.level", "diode.level.ron"),
+                   Some("[Gates] after Signals, diode.level.ron".to_owned()));
+        assert_eq!(rename_level("[Gates] after Signals", "diode.level", "diode.level.ron"), None);
+        assert_eq!(rename_level("# diode.level", "diode.level", "diode.level.ron"), None);
+        assert_eq!(level_file_name("[Gates] after Signals"), None);
+    }
+
+    #[test]
+    fn levels_are_unlocked_by_solved_prerequisites() {
+        let packs = LevelPacks::parse(MANIFEST);
+        let mut progress = Progress::default();
+
+        assert!(packs.is_unlocked("free.level", &progress));
+        assert!(!packs.is_unlocked("diode.level", &progress));
+        assert_eq!(packs.next_level("free.level", &progress), Some("crossroad.level"));
+        assert_eq!(packs.next_level("forward.level", &progress), None);
+
+        progress.solve("crossroad.level");
+        progress.solve("forward.level");
+        assert_eq!(packs.next_level("forward.level", &progress), Some("diode.level"));
+        assert!(!packs.is_unlocked("blocker.level", &progress));
+        progress.solve("diode.level");
+        assert!(packs.is_unlocked("blocker.level", &progress));
+        assert!(!packs.is_unlocked("or.level", &progress));
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::world::components::{CellType, Point};
+use crate::world::components::CellType::{ELECTRON, TAIL, WIRE};
+use crate::world::simulation::Simulation;
+
+/// Count of generations shown on the waveform panel.
+pub const TRACE_LENGTH: usize = 64;
+
+/// State of a probed cell at one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Electron,
+    Tail,
+    Wire,
+    Empty,
+}
+
+impl From<&CellType> for Signal {
+    fn from(cell_type: &CellType) -> Self {
+        match cell_type {
+            ELECTRON(_) => Signal::Electron,
+            TAIL(_) => Signal::Tail,
+            WIRE(_) => Signal::Wire,
+            _others => Signal::Empty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    pub name: String,
+    pub position: Point,
+    /// Probes of outputs are attached by the exercise, the player can't remove them.
+    pub output: bool,
+    /// Signals of the last ticks, the newest one is at the back.
+    pub trace: VecDeque<Signal>,
+}
+
+/// Named probes of the logic analyzer, they sample their cells after every generation.
+#[derive(Resource, Debug)]
+pub struct Probes {
+    pub probes: Vec<Probe>,
+    /// Changes when probes are attached or removed, the panel lays out its rows again.
+    pub revision: usize,
+    /// Whether the waveform panel is shown while there are probes.
+    pub panel: bool,
+    next: usize,
+}
+
+impl Default for Probes {
+    fn default() -> Self {
+        Probes {
+            probes: Vec::new(),
+            revision: 0,
+            panel: true,
+            next: 1,
+        }
+    }
+}
+
+impl Probes {
+    /// Attaches a probe to a wire cell or removes the probe of the player from it.
+    /// Returns the name of the attached probe.
+    pub fn toggle(&mut self, point: &Point, simulation: &Simulation) -> Option<String> {
+        if let Some(index) = self.probes.iter().position(|probe| !probe.output && probe.position == *point) {
+            self.probes.remove(index);
+            self.revision += 1;
+            return None;
+        }
+
+        let cell_type = simulation.get_cell(point);
+        if Signal::from(&cell_type) == Signal::Empty {
+            return None;
+        }
+        let name = format!("P{}", self.next);
+        self.next += 1;
+        self.attach(name.clone(), point, false, &cell_type);
+
+        Some(name)
+    }
+
+    /// Replaces probes of outputs with probes of the outputs of a new exercise.
+    pub fn set_outputs(&mut self, outputs: &[Point], simulation: &Simulation) {
+        self.probes.retain(|probe| !probe.output);
+        for (index, point) in outputs.iter().enumerate() {
+            self.attach(format!("Out {}", index + 1), point, true, &simulation.get_cell(point));
+        }
+        self.revision += 1;
+    }
+
+    /// Samples every probe after a generation.
+    pub fn sample(&mut self, simulation: &Simulation) {
+        for probe in self.probes.iter_mut() {
+            probe.trace.push_back(Signal::from(&simulation.get_cell(&probe.position)));
+            if probe.trace.len() > TRACE_LENGTH {
+                probe.trace.pop_front();
+            }
+        }
+    }
+
+    /// Forgets the last sample when the board steps back.
+    pub fn unsample(&mut self) {
+        for probe in self.probes.iter_mut() {
+            if probe.trace.len() > 1 {
+                probe.trace.pop_back();
+            }
+        }
+    }
+
+    /// Starts traces again from the current board, for example when an exercise starts.
+    pub fn reset(&mut self, simulation: &Simulation) {
+        for probe in self.probes.iter_mut() {
+            probe.trace = VecDeque::from([Signal::from(&simulation.get_cell(&probe.position))]);
+        }
+    }
+
+    /// Removes all probes of the previous board.
+    pub fn clear(&mut self) {
+        self.probes.clear();
+        self.next = 1;
+        self.revision += 1;
+    }
+
+    fn attach(&mut self, name: String, point: &Point, output: bool, cell_type: &CellType) {
+        self.probes.push(Probe {
+            name,
+            position: point.clone(),
+            output,
+            trace: VecDeque::from([Signal::from(cell_type)]),
+        });
+        self.revision += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::resources::World;
+
+    fn simulation() -> Simulation {
+        let level = "6 3 dead\ntrue\ne e e e e e\ne a w w w e\ne e e e e e\n0\n";
+        Simulation::new(&World::from_string(level.to_owned()).unwrap())
+    }
+
+    #[test]
+    fn probes_attach_to_wires_only() {
+        let simulation = simulation();
+        let mut probes = Probes::default();
+
+        assert_eq!(probes.toggle(&Point(0, 0), &simulation), None);
+        assert_eq!(probes.toggle(&Point(2, 1), &simulation), Some("P1".to_owned()));
+        probes.set_outputs(&[Point(4, 1)], &simulation);
+        assert_eq!(probes.probes.len(), 2);
+
+        assert_eq!(probes.toggle(&Point(4, 1), &simulation), Some("P2".to_owned()));
+        assert_eq!(probes.toggle(&Point(4, 1), &simulation), None);
+        assert_eq!(probes.toggle(&Point(2, 1), &simulation), None);
+        assert_eq!(probes.probes.iter().map(|probe| probe.name.as_str()).collect::<Vec<_>>(), vec!["Out 1"]);
+    }
+
+    #[test]
+    fn traces_follow_generations() {
+        let mut simulation = simulation();
+        let mut probes = Probes::default();
+        probes.toggle(&Point(2, 1), &simulation);
+        probes.set_outputs(&[Point(4, 1)], &simulation);
+
+        for _ in 0..3 {
+            simulation.step();
+            probes.sample(&simulation);
+        }
+        assert_eq!(probes.probes[0].trace, [Signal::Wire, Signal::Electron, Signal::Tail, Signal::Wire]);
+        assert_eq!(probes.probes[1].trace, [Signal::Wire, Signal::Wire, Signal::Wire, Signal::Electron]);
+
+        probes.unsample();
+        assert_eq!(probes.probes[1].trace, [Signal::Wire, Signal::Wire, Signal::Wire]);
+
+        for _ in 0..TRACE_LENGTH {
+            probes.sample(&simulation);
+        }
+        assert_eq!(probes.probes[0].trace.len(), TRACE_LENGTH);
+        probes.reset(&simulation);
+        assert_eq!(probes.probes[0].trace.len(), 1);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::world::components::{CellType, Point};
+use crate::world::components::CellType::{ELECTRON, TAIL, WIRE};
+use crate::world::score::Score;
+use crate::world::simulation::Simulation;
+
+/// Version written by `Progress::to_ron`. Files with a greater version are rejected.
+pub const PROGRESS_FORMAT_VERSION: u32 = 1;
+
+/// Name of the save file on native builds, it is kept next to the assets directory.
+pub const PROGRESS_FILE: &str = "progress.ron";
+
+/// Key of the save in the local storage of the browser.
+pub const PROGRESS_STORAGE_KEY: &str = "wire_world.progress";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelProgress {
+    pub solved: bool,
+    /// Last board of the player as rows of level cell symbols.
+    pub board: Option<Vec<String>>,
+    pub best: Option<Score>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProgressFile {
+    pub version: u32,
+    pub levels: BTreeMap<String, LevelProgress>,
+}
+
+/// Progress of the player by level file names, it survives restarts of the game.
+#[derive(Resource, Debug, Default)]
+pub struct Progress {
+    pub levels: BTreeMap<String, LevelProgress>,
+    /// Progress is changed since it was saved.
+    pub dirty: bool,
+}
+
+impl Progress {
+    pub fn level(&self, level: &str) -> Option<&LevelProgress> {
+        self.levels.get(level)
+    }
+
+    pub fn is_solved(&self, level: &str) -> bool {
+        self.level(level).map(|progress| progress.solved).unwrap_or_default()
+    }
+
+    pub fn solve(&mut self, level: &str) {
+        self.levels.entry(level.to_owned()).or_default().solved = true;
+        self.dirty = true;
+    }
+
+    /// Updates personal bests of the level and returns the bests before the score.
+    pub fn record_score(&mut self, level: &str, score: &Score) -> Option<Score> {
+        let progress = self.levels.entry(level.to_owned()).or_default();
+        let previous = progress.best;
+        progress.best = Some(score.best(previous.as_ref()));
+        self.dirty = true;
+
+        previous
+    }
+
+    /// Remembers the board of the player. Electrons and tails stay only where the level allows
+    /// the player to place them, otherwise they are left by a run and become wires.
+    pub fn keep_board(&mut self, level: &str, simulation: &Simulation, electron_available: bool) {
+        let (width, height) = simulation.size();
+        let rows = (0..height)
+            .map(|y| (0..width)
+                .map(|x| match (simulation.get_cell(&Point(x, y)), electron_available) {
+                    (ELECTRON(fixed) | TAIL(fixed), false) if !fixed => WIRE(false).symbol(),
+                    (cell_type, _) => cell_type.symbol(),
+                })
+                .collect())
+            .collect();
+
+        self.levels.entry(level.to_owned()).or_default().board = Some(rows);
+        self.dirty = true;
+    }
+
+    /// Moves the progress of a level to its new file name, when the editor saves a legacy level as RON.
+    pub fn rename_level(&mut self, old: &str, new: &str) {
+        if let Some(progress) = self.levels.remove(old) {
+            self.levels.insert(new.to_owned(), progress);
+            self.dirty = true;
+        }
+    }
+
+    pub fn to_ron(&self) -> anyhow::Result<String> {
+        let file = ProgressFile {
+            version: PROGRESS_FORMAT_VERSION,
+            levels: self.levels.clone(),
+        };
+
+        Ok(ron::ser::to_string_pretty(&file, PrettyConfig::new().depth_limit(3))?)
+    }
+
+    pub fn from_ron(s: &str) -> anyhow::Result<Progress> {
+        let file: ProgressFile = ron::from_str(s)?;
+        if file.version > PROGRESS_FORMAT_VERSION {
+            anyhow::bail!("Progress version {} is newer than supported version {PROGRESS_FORMAT_VERSION}", file.version);
+        }
+
+        Ok(Progress { levels: file.levels, dirty: false })
+    }
+}
+
+/// Puts free cells of a saved board onto the board of the level and returns the changed cells.
+/// Nothing is restored when the level has changed its size since the board was saved,
+/// fixed cells of the level always stay.
+pub fn restore_board(simulation: &mut Simulation, rows: &[String]) -> Vec<Point> {
+    let (width, height) = simulation.size();
+    if rows.len() != height || rows.iter().any(|row| row.chars().count() != width) {
+        return Vec::new();
+    }
+
+    let mut changed = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, symbol) in row.chars().enumerate() {
+            let point = Point(x, y);
+            let current = simulation.get_cell(&point);
+            match CellType::from_symbol(symbol) {
+                Some(saved) if !saved.is_fixed() && !current.is_fixed() && saved != current => {
+                    simulation.set_cell(&point, saved);
+                    changed.push(point);
+                }
+                _others => {}
+            }
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::CellType::EMPTY;
+    use crate::world::resources::World;
+
+    fn simulation(rows: &str) -> Simulation {
+        let level = format!("3 2 dead\ntrue\n{rows}\n0\n");
+        Simulation::new(&World::from_string(level).unwrap())
+    }
+
+    #[test]
+    fn board_is_restored_around_fixed_cells() {
+        let mut progress = Progress::default();
+        let mut board = simulation("w a W\ne e e");
+        board.set_cell(&Point(1, 1), TAIL(false));
+        progress.keep_board("a.level.ron", &board, false);
+        assert_eq!(progress.level("a.level.ron").unwrap().board, Some(vec!["wwW".to_owned(), "ewe".to_owned()]));
+
+        let saved = vec!["wwe".to_owned(), "awW".to_owned()];
+        let mut level = simulation("e e W\ne e e");
+        let changed = restore_board(&mut level, &saved);
+        assert_eq!(changed, vec![Point(0, 0), Point(1, 0), Point(0, 1), Point(1, 1)]);
+        assert_eq!(level.get_cell(&Point(2, 0)), WIRE(true));
+        assert_eq!(level.get_cell(&Point(2, 1)), EMPTY(false));
+
+        assert!(restore_board(&mut level, &["ww".to_owned()]).is_empty());
+    }
+
+    #[test]
+    fn progress_round_trip() {
+        let mut progress = Progress::default();
+        progress.solve("b.level.ron");
+        assert_eq!(progress.record_score("b.level.ron", &Score { cells: 4, ticks: 9, area: 6 }), None);
+        let previous = progress.record_score("b.level.ron", &Score { cells: 3, ticks: 12, area: 6 });
+        assert_eq!(previous, Some(Score { cells: 4, ticks: 9, area: 6 }));
+        progress.keep_board("a.level.ron", &simulation("w a W\ne e e"), true);
+        assert!(progress.dirty);
+
+        let saved = Progress::from_ron(&progress.to_ron().unwrap()).unwrap();
+        assert!(saved.is_solved("b.level.ron"));
+        assert!(!saved.is_solved("a.level.ron"));
+        assert_eq!(saved.level("b.level.ron").unwrap().best, Some(Score { cells: 3, ticks: 9, area: 6 }));
+        assert_eq!(saved.levels, progress.levels);
+        assert!(Progress::from_ron("(version: 99, levels: {})").is_err());
+    }
+
+    #[test]
+    fn progress_follows_renamed_level() {
+        let mut progress = Progress::default();
+        progress.solve("diode.level");
+        progress.record_score("diode.level", &Score { cells: 4, ticks: 9, area: 6 });
+        progress.dirty = false;
+
+        progress.rename_level("diode.level", "diode.level.ron");
+        assert!(progress.dirty);
+        assert!(progress.is_solved("diode.level.ron"));
+        assert_eq!(progress.level("diode.level"), None);
+        assert_eq!(progress.level("diode.level.ron").unwrap().best, Some(Score { cells: 4, ticks: 9, area: 6 }));
+
+        progress.dirty = false;
+        progress.rename_level("or.level", "or.level.ron");
+        assert!(!progress.dirty);
+        assert_eq!(progress.level("or.level.ron"), None);
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::world::components::{CellType, Point};
+use crate::world::components::CellType::{ELECTRON, TAIL, WIRE};
+use crate::world::simulation::Simulation;
+
+/// Cells changed from one tick of the exercise to the next one, with states before and after.
+/// Spawns at the next tick belong to the generation too.
+pub type Generation = Vec<(Point, CellType, CellType)>;
+
+/// Every generation of the last exercise run. It outlives a failed exercise, so the run can be
+/// scrubbed on the timeline. Recorded states are only painted, the simulation keeps the live board.
+#[derive(Resource, Debug, Default)]
+pub struct Recording {
+    /// Exercise of the run, `None` before the first run.
+    pub exercise: Option<usize>,
+    generations: Vec<Generation>,
+    /// Tick shown on the board, `None` shows the live board.
+    pub view: Option<usize>,
+    /// Tick painted on the board now.
+    shown: usize,
+    /// Positions of cells in the last generation, built by the first `record` after a generation.
+    last: HashMap<Point, usize>,
+}
+
+impl Recording {
+    /// Forgets the previous run.
+    pub fn start(&mut self, exercise: usize) {
+        self.exercise = Some(exercise);
+        self.generations.clear();
+        self.view = None;
+        self.shown = 0;
+        self.last.clear();
+    }
+
+    /// Forgets the run of the previous level.
+    pub fn clear(&mut self) {
+        *self = Recording::default();
+    }
+
+    /// Count of recorded ticks, the run can be shown at ticks from 0 to `len`.
+    pub fn len(&self) -> usize {
+        self.generations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generations.is_empty()
+    }
+
+    /// Tick painted on the board now.
+    pub fn shown(&self) -> usize {
+        self.shown
+    }
+
+    /// Records cells changed by a generation of the live board, their previous states follow from the rules.
+    pub fn record_step(&mut self, simulation: &Simulation, changed: &[Point]) {
+        self.generations.push(changed.iter()
+            .map(|point| {
+                let after = simulation.get_cell(point);
+                let before = match &after {
+                    ELECTRON(fixed) => WIRE(*fixed),
+                    TAIL(fixed) => ELECTRON(*fixed),
+                    WIRE(fixed) => TAIL(*fixed),
+                    other => other.clone(),
+                };
+                (point.clone(), before, after)
+            })
+            .collect());
+        self.shown = self.len();
+        self.last.clear();
+    }
+
+    /// Records a cell changed after the last generation, like a spawned electron.
+    pub fn record(&mut self, point: &Point, before: CellType, after: CellType) {
+        if let Some(generation) = self.generations.last_mut() {
+            if self.last.is_empty() {
+                self.last = generation.iter().enumerate()
+                    .map(|(position, (recorded, _, _))| (recorded.clone(), position))
+                    .collect();
+            }
+            match self.last.get(point) {
+                Some(&position) => generation[position].2 = after,
+                None => {
+                    self.last.insert(point.clone(), generation.len());
+                    generation.push((point.clone(), before, after));
+                }
+            }
+        }
+    }
+
+    /// Forgets the last generation when the live board steps back.
+    pub fn pop(&mut self) {
+        self.generations.pop();
+        self.last.clear();
+        self.shown = self.len();
+        self.view = self.view.filter(|tick| *tick <= self.len());
+    }
+
+    /// Cells to paint for showing the given tick instead of the shown one.
+    pub fn seek(&mut self, tick: usize) -> Vec<(Point, CellType)> {
+        let tick = tick.min(self.len());
+        let cells = match tick < self.shown {
+            true => self.generations[tick..self.shown].iter().rev()
+                .flat_map(|generation| generation.iter().map(|(point, before, _)| (point.clone(), before.clone())))
+                .collect(),
+            false => self.generations[self.shown..tick].iter()
+                .flat_map(|generation| generation.iter().map(|(point, _, after)| (point.clone(), after.clone())))
+                .collect(),
+        };
+        self.shown = tick;
+
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::resources::World;
+
+    #[test]
+    fn seek_paints_recorded_generations() {
+        let level = "6 3 dead\ntrue\ne e e e e e\ne a w w w e\ne e e e e e\n0\n";
+        let mut simulation = Simulation::new(&World::from_string(level.to_owned()).unwrap());
+        let start = simulation.clone();
+        let mut recording = Recording::default();
+        recording.start(0);
+
+        for _ in 0..3 {
+            let changed = simulation.step();
+            recording.record_step(&simulation, &changed);
+        }
+        recording.record(&Point(0, 0), simulation.get_cell(&Point(0, 0)), ELECTRON(false));
+        let count = recording.generations[2].len();
+        recording.record(&Point(0, 0), ELECTRON(false), ELECTRON(false));
+        assert_eq!(recording.generations[2].len(), count);
+        simulation.set_cell(&Point(0, 0), ELECTRON(false));
+        let end = simulation.clone();
+        assert_eq!(recording.len(), 3);
+
+        let mut board = simulation.clone();
+        for (point, cell_type) in recording.seek(0) {
+            board.set_cell(&point, cell_type);
+        }
+        for y in 0..3 {
+            for x in 0..6 {
+                assert_eq!(board.get_cell(&Point(x, y)), start.get_cell(&Point(x, y)));
+            }
+        }
+
+        for (point, cell_type) in recording.seek(1) {
+            board.set_cell(&point, cell_type);
+        }
+        let mut second = start.clone();
+        second.step();
+        assert_eq!(board.get_cell(&Point(2, 1)), second.get_cell(&Point(2, 1)));
+        assert_eq!(board.get_cell(&Point(1, 1)), TAIL(false));
+
+        for (point, cell_type) in recording.seek(5) {
+            board.set_cell(&point, cell_type);
+        }
+
+        for y in 0..3 {
+            for x in 0..6 {
+                assert_eq!(board.get_cell(&Point(x, y)), end.get_cell(&Point(x, y)));
+            }
+        }
+        assert_eq!(recording.shown(), 3);
+    }
+
+    #[test]
+    fn pop_keeps_view_inside_recording() {
+        let mut recording = Recording::default();
+        recording.start(1);
+        recording.generations = vec![Vec::new(); 3];
+        recording.view = Some(3);
+
+        recording.pop();
+        assert_eq!(recording.len(), 2);
+        assert_eq!(recording.view, None);
+        assert_eq!(recording.shown(), 2);
+    }
+}
@@ -1,27 +1,54 @@
-use anyhow::Error;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
 use bevy::prelude::*;
 use bevy::reflect::{TypePath, TypeUuid};
-use crate::world::components::{CellType, Point};
+use crate::world::components::{Boundary, CellType, Point};
 use crate::world::components::CellType::{ELECTRON, EMPTY, WIRE};
+use crate::world::diagnostics::{LevelError, Line, Source};
+use crate::world::packs::LevelPacks;
+use crate::world::simulation::Simulation;
 
 #[derive(Resource, Debug, Clone)]
 pub struct WorldState {
     pub size: (usize, usize),
-    pub map: Vec<Entity>,
+    pub simulation: Simulation,
     pub exercises: Vec<ExerciseData>,
     pub electron_available: bool,
     pub lock: bool,
 }
 
-#[derive(TypeUuid, TypePath, Debug)]
+#[derive(TypeUuid, TypePath, Debug, Clone)]
 #[uuid = "e51081d0-6168-4881-a1c6-4249b2000d7f"]
 pub struct World {
+    pub title: String,
     pub size: (usize, usize),
+    pub boundary: Boundary,
     pub map: Vec<CellType>,
     pub electron_available: bool,
     pub exercises: Vec<ExerciseData>,
 }
 
+/// Ordered list of level files shown in the level menu.
+#[derive(TypeUuid, TypePath, Debug)]
+#[uuid = "fbd71f87-4272-4c0a-a554-97a00998e67f"]
+pub struct LevelManifest {
+    pub levels: Vec<String>,
+    pub packs: LevelPacks,
+}
+
+#[derive(Resource, Default)]
+pub struct LevelsCatalog {
+    pub manifest: Handle<LevelManifest>,
+    pub levels: Vec<(String, Handle<World>)>,
+    pub packs: LevelPacks,
+}
+
+/// Errors of level files which failed to load, keyed by their asset paths.
+/// The asset loader shares it with the game, because a failed load produces no asset to read the error from.
+#[derive(Resource, Clone, Default)]
+pub struct LevelLoadErrors(Arc<Mutex<HashMap<String, String>>>);
+
 #[derive(Debug, Clone)]
 pub struct ExerciseData {
     pub description: String,
@@ -38,34 +65,78 @@ pub struct Counter {
 #[derive(Resource)]
 pub struct LevelConfig {
     pub level_name: Option<String>,
+    pub handle: Option<Handle<World>>,
 }
 
 impl LevelConfig {
     pub fn empty() -> LevelConfig {
         LevelConfig {
-            level_name: None
+            level_name: None,
+            handle: None,
+        }
+    }
+}
+
+impl LevelLoadErrors {
+    pub fn get(&self, path: &str) -> Option<String> {
+        self.0.lock().ok()?.get(path).cloned()
+    }
+
+    pub fn insert(&self, path: String, error: String) {
+        if let Ok(mut errors) = self.0.lock() {
+            errors.insert(path, error);
+        }
+    }
+
+    pub fn remove(&self, path: &str) {
+        if let Ok(mut errors) = self.0.lock() {
+            errors.remove(path);
+        }
+    }
+}
+
+impl LevelManifest {
+    pub fn from_string(s: String) -> LevelManifest {
+        let packs = LevelPacks::parse(&s);
+        LevelManifest {
+            levels: packs.levels(),
+            packs,
         }
     }
 }
 
 impl World {
-    pub fn from_string(s: String) -> anyhow::Result<World, Error> {
-        let mut lines = s.lines();
-        let mut sizes = lines.next().ok_or(Error::msg("Not found size"))?.split(" ");
-        let width: usize = sizes.next().ok_or(Error::msg("Not found width"))?.parse()?;
-        let height: usize = sizes.next().ok_or(Error::msg("Not found height"))?.parse()?;
-        let electron_available: bool = lines.next()
-            .ok_or(Error::msg("Not found electron-available flag"))?.parse()?;
+    pub fn from_string(s: String) -> Result<World, LevelError> {
+        let mut source = Source::new(&s);
+        let sizes = source.next_line("size")?;
+        let width: usize = sizes.parse(0, "width")?;
+        let height: usize = sizes.parse(1, "height")?;
+        let boundary: Boundary = match sizes.fields().get(2) {
+            Some(_) => sizes.parse(2, "boundary mode")?,
+            None => Boundary::default(),
+        };
+        let electron_available: bool = source.next_line("electron-available flag")?
+            .parse(0, "electron-available flag")?;
 
         let mut map: Vec<CellType> = Vec::with_capacity(width * height);
-        for _i in 0..height {
-            let line = lines.next().ok_or(Error::msg("File is broken"))?;
+        for i in 0..height {
+            let line = source.next_line("row of the board")?;
+            let cells = line.fields();
+            if cells.len() < width {
+                return Err(line.error(
+                    line.text.chars().count() + 1,
+                    format!("Row {i} has {} cells but width is {width}", cells.len()),
+                ));
+            }
+            if let Some(extra) = cells.get(width) {
+                return Err(line.error(
+                    extra.column,
+                    format!("Row {i} has {} cells but width is {width}", cells.len()),
+                ));
+            }
 
-            for (j, cell) in line.split(" ").enumerate() {
-                if j >= width {
-                    break
-                }
-                map.push(match cell {
+            for cell in cells.iter() {
+                map.push(match cell.text {
                     "a" => ELECTRON(false),
                     "w" => WIRE(false),
                     "A" => ELECTRON(true),
@@ -76,53 +147,56 @@ impl World {
             }
         }
 
-        let exercise_count: usize = lines.next()
-            .ok_or(Error::msg("Not found count of exercises"))?.parse()?;
+        let exercise_count: usize = source.next_line("count of exercises")?
+            .parse(0, "count of exercises")?;
         let mut exercises: Vec<ExerciseData> = Vec::with_capacity(exercise_count);
 
         for i in 0..exercise_count {
             let mut description = String::new();
-            while let Some(line) = lines.next() {
-                if line.is_empty() {
-                    description.remove(description.len()-1);
+            loop {
+                let line = source.next_line(&format!("empty line after description of exercise {i}"))?;
+                if line.text.is_empty() {
                     break;
-                };
+                }
 
-                description.push_str(line);
-                description.push('\n');
+                if !description.is_empty() {
+                    description.push('\n');
+                }
+                description.push_str(line.text);
             }
 
-            let timeout: usize = lines.next()
-                .ok_or(Error::msg(format!("Not found timeout of exercise {i}")))?
-                .parse()?;
+            let timeout: usize = source.next_line(&format!("timeout of exercise {i}"))?
+                .parse(0, "timeout")?;
 
-            let spawns_count: usize = lines.next()
-                .ok_or(Error::msg("Not found count of electron spawns"))?.parse()?;
+            let spawns_count: usize = source.next_line("count of electron spawns")?
+                .parse(0, "count of electron spawns")?;
             let mut spawns: Vec<(Point, usize)> = Vec::with_capacity(spawns_count);
             for _ in 0..spawns_count {
-                spawns.push(
-                    Self::parse_electron_spawn(lines.next()
-                            .ok_or(Error::msg("Not found line with electron spawn"))?
-                    )?
-                );
+                spawns.push(Self::parse_electron_spawn(source.next_line("line with electron spawn")?)?);
             }
 
-            let outputs_count: usize = lines.next()
-                .ok_or(Error::msg("Not found count of outputs"))?.parse()?;
+            let outputs_count: usize = source.next_line("count of outputs")?
+                .parse(0, "count of outputs")?;
             let mut outputs: Vec<(Point, bool, usize, usize)> = Vec::with_capacity(outputs_count);
             for _ in 0..outputs_count {
-                outputs.push(
-                    Self::parse_output(lines.next()
-                        .ok_or(Error::msg("Not found line with output"))?
-                    )?
-                );
+                outputs.push(Self::parse_output(source.next_line("line with output")?)?);
             }
             exercises.push(ExerciseData { description, timeout, spawns, outputs });
         }
+        if let Some(line) = source.next_non_empty() {
+            return Err(line.error(1, format!("Found more exercises than declared count {exercise_count}")));
+        }
+
+        let title = exercises.first()
+            .and_then(|exercise| exercise.description.lines().next())
+            .unwrap_or_default()
+            .to_owned();
 
         Ok(
             World {
+                title,
                 size: (width, height),
+                boundary,
                 map,
                 exercises,
                 electron_available
@@ -138,59 +212,21 @@ impl World {
         self.map[self.index(point)].clone()
     }
 
-    fn parse_electron_spawn(line: &str) -> Result<(Point, usize), Error> {
-        let mut sizes = line.split(" ");
-        let instant: usize = sizes.next()
-            .ok_or(Error::msg("Not found instant of electron spawn"))?.parse()?;
-        let x: usize = sizes.next()
-            .ok_or(Error::msg("Not found x of electron spawn"))?.parse()?;
-        let y: usize = sizes.next()
-            .ok_or(Error::msg("Not found y of electron spawn"))?.parse()?;
+    fn parse_electron_spawn(line: Line) -> Result<(Point, usize), LevelError> {
+        let instant: usize = line.parse(0, "instant of electron spawn")?;
+        let x: usize = line.parse(1, "x of electron spawn")?;
+        let y: usize = line.parse(2, "y of electron spawn")?;
 
         Ok((Point(x, y), instant))
     }
 
-    fn parse_output(line: &str) -> Result<(Point, bool, usize, usize), Error> {
-        let mut output = line.split(" ");
-        let expectation: bool = output.next()
-            .ok_or(Error::msg("Not found from expectation of output"))?.parse()?;
-        let from: usize = output.next()
-            .ok_or(Error::msg("Not found from instant of output"))?.parse()?;
-        let until: usize = output.next()
-            .ok_or(Error::msg("Not found until instant of output"))?.parse()?;
-        let x: usize = output.next()
-            .ok_or(Error::msg("Not found x of output"))?.parse()?;
-        let y: usize = output.next()
-            .ok_or(Error::msg("Not found y of output"))?.parse()?;
+    fn parse_output(line: Line) -> Result<(Point, bool, usize, usize), LevelError> {
+        let expectation: bool = line.parse(0, "expectation of output")?;
+        let from: usize = line.parse(1, "from instant of output")?;
+        let until: usize = line.parse(2, "until instant of output")?;
+        let x: usize = line.parse(3, "x of output")?;
+        let y: usize = line.parse(4, "y of output")?;
 
         Ok((Point(x, y), expectation, from, until))
     }
 }
-
-impl WorldState {
-    pub fn index(&self, point: &Point) -> usize {
-        point.1 * self.size.0 + point.0
-    }
-
-    pub fn get_cell(&self, point: &Point) -> Entity {
-        self.map[self.index(point)]
-    }
-
-    pub fn get_cells_around(&self, point: &Point) -> Vec<Entity> {
-        let mut found = Vec::new();
-        const OFFSETS: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1),
-            (0, -1), (0, 1), (1, -1),
-            (1, 0), (1, 1)];
-
-        for offset in OFFSETS.iter() {
-            let pos = Point(
-                ((point.0 as isize + offset.0 + self.size.0 as isize) as usize) % self.size.0,
-                ((point.1 as isize + offset.1 + self.size.1 as isize) as usize) % self.size.1,
-            );
-
-            found.push(self.get_cell(&pos));
-        }
-
-        found
-    }
-}
\ No newline at end of file
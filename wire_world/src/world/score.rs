@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::world::components::{CellType, Point};
+use crate::world::components::CellType::{ELECTRON, TAIL, WIRE};
+use crate::world::simulation::Simulation;
+
+/// Numbers of a solution, every number is better when it is lower.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    /// Wires placed by the player on empty cells of the level.
+    pub cells: usize,
+    /// Ticks of all exercises.
+    pub ticks: usize,
+    /// Area of the bounding box of cells changed by the player.
+    pub area: usize,
+}
+
+/// Ticks of the exercises passed in the current run and the score of the solved level.
+#[derive(Resource, Debug, Default)]
+pub struct Scoring {
+    pub ticks: usize,
+    pub score: Option<Score>,
+    /// Personal bests before the level was solved this time.
+    pub best: Option<Score>,
+}
+
+fn is_conductor(cell_type: &CellType) -> bool {
+    matches!(cell_type, WIRE(_) | ELECTRON(_) | TAIL(_))
+}
+
+impl Score {
+    /// Compares the board with the board of the level. Electrons moving along wires don't
+    /// matter, only whether a free cell conducts.
+    pub fn measure(level: &Simulation, board: &Simulation, ticks: usize) -> Score {
+        let (width, height) = board.size();
+        let mut cells = 0;
+        let mut changed: Option<(Point, Point)> = None;
+
+        for y in 0..height {
+            for x in 0..width {
+                let point = Point(x, y);
+                let (before, after) = (level.get_cell(&point), board.get_cell(&point));
+                if before.is_fixed() || is_conductor(&before) == is_conductor(&after) {
+                    continue;
+                }
+
+                if is_conductor(&after) {
+                    cells += 1;
+                }
+                changed = Some(match changed {
+                    Some((min, max)) => (Point(min.0.min(x), min.1.min(y)), Point(max.0.max(x), max.1.max(y))),
+                    None => (point.clone(), point),
+                });
+            }
+        }
+
+        Score {
+            cells,
+            ticks,
+            area: changed.map(|(min, max)| (max.0 - min.0 + 1) * (max.1 - min.1 + 1)).unwrap_or_default(),
+        }
+    }
+
+    /// Personal bests after this score, every number is kept separately.
+    pub fn best(&self, previous: Option<&Score>) -> Score {
+        match previous {
+            Some(previous) => Score {
+                cells: self.cells.min(previous.cells),
+                ticks: self.ticks.min(previous.ticks),
+                area: self.area.min(previous.area),
+            },
+            None => *self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::resources::World;
+
+    fn simulation(rows: &str) -> Simulation {
+        let level = format!("4 3 dead\ntrue\n{rows}\n0\n");
+        Simulation::new(&World::from_string(level).unwrap())
+    }
+
+    #[test]
+    fn score_counts_player_cells() {
+        let level = simulation("W e e w\ne e e w\ne E e e");
+        let board = simulation("W w a e\ne e w w\ne E e e");
+
+        assert_eq!(Score::measure(&level, &board, 17), Score { cells: 3, ticks: 17, area: 6 });
+        assert_eq!(Score::measure(&level, &level, 0).area, 0);
+    }
+
+    #[test]
+    fn bests_are_kept_separately() {
+        let score = Score { cells: 5, ticks: 30, area: 12 };
+        let previous = Score { cells: 7, ticks: 20, area: 12 };
+
+        assert_eq!(score.best(Some(&previous)), Score { cells: 5, ticks: 20, area: 12 });
+        assert_eq!(score.best(None), score);
+    }
+}
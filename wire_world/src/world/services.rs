@@ -1,4 +1,3 @@
-use std::ops::{Mul};
 use std::time::Duration;
 
 use bevy::asset::{AssetEvent, AssetServer};
@@ -9,18 +8,114 @@ use bevy::sprite::Anchor::{BottomCenter};
 use bevy::text::{BreakLineOn, Text2dBounds};
 
 
-use crate::control::{ClickEvent, MoveCamera};
+use crate::control::{ClickEvent, CursorPosition, DragEvent, DragPhase};
+use crate::control::MoveCamera;
+use crate::control::bindings::{KeyAction, KeyBindings};
+use crate::control::camera::{BoardBounds, FitBoard, view_rect};
 use crate::{GameState, LevelState};
 use crate::world::CELL_SIZE;
-use crate::world::components::{Cell, Change, ChangeExercise, ElectronSpawn, Exercise, ExpectedOutput, NextUpdate, OutputStatus, Point};
-use crate::world::components::CellType::{ELECTRON, EMPTY, TAIL, WIRE};
-use crate::world::components::OutputStatus::{Fail, Inactive, Success, Waiting};
-use crate::world::resources::{Counter, LevelConfig, World, WorldState};
+use crate::world::board::{BoardTexture, board_rect, cell_at, cell_center};
+use crate::world::components::{Change, ChangeExercise, CursorMark, Edit, ElectronSpawn, Exercise, ExpectedOutput, FailureMark, OpenLevel, OutputStatus, Point, ProbeMark, StrokePreview};
+use crate::world::components::CellType::{ELECTRON, EMPTY, WIRE};
+use crate::world::components::OutputStatus::{Fail, Inactive, Success};
+use crate::world::resources::{Counter, LevelConfig, LevelManifest, LevelsCatalog, World, WorldState};
+use crate::world::failure::Failure;
+use crate::world::history::{EditHistory, cell_edits};
+use crate::world::simulation::Simulation;
+use crate::world::probes::Probes;
+use crate::world::progress::{Progress, restore_board};
+use crate::world::storage::{read_progress, write_progress};
+use crate::world::recording::Recording;
+use crate::world::score::{Score, Scoring};
+use crate::world::stepper::{Move, Stepper, restore};
+use crate::world::tools::{Stroke, Tool, Toolbox, rect};
 use crate::world::tweens::{blink_background};
 
+const SELECTION_COLOR: Color = Color::CYAN;
+const PROBE_COLOR: Color = Color::FUCHSIA;
+const FAILURE_COLOR: Color = Color::RED;
+const CURSOR_COLOR: Color = Color::WHITE;
+
+/// Seconds without changes of the progress before it is written, so painting doesn't write on every stroke.
+const SAVE_DELAY: f32 = 1.0;
+
+pub fn load_levels_manifest(
+    mut catalog: ResMut<LevelsCatalog>,
+    assets: Res<AssetServer>,
+) {
+    catalog.manifest = assets.load("levels.manifest");
+}
+
+pub fn load_levels_catalog(
+    mut catalog: ResMut<LevelsCatalog>,
+    mut manifest_events: EventReader<AssetEvent<LevelManifest>>,
+    manifests: Res<Assets<LevelManifest>>,
+    assets: Res<AssetServer>,
+) {
+    for event in manifest_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if let Some(manifest) = manifests.get(handle) {
+                    info!("Levels manifest is loaded {:?}", manifest);
+                    catalog.levels = manifest.levels.iter()
+                        .map(|file_name| (file_name.clone(), assets.load(file_name.as_str())))
+                        .collect();
+                    catalog.packs = manifest.packs.clone();
+                }
+            }
+            _others => {}
+        }
+    }
+}
+
+/// Reads the progress saved in previous sessions.
+pub fn load_progress(
+    mut progress: ResMut<Progress>,
+) {
+    match read_progress() {
+        Ok(Some(saved)) => {
+            info!("Progress of {} levels is loaded", saved.levels.len());
+            *progress = saved;
+        }
+        Ok(None) => {}
+        Err(error) => error!("Failed to load progress: {}", error),
+    }
+}
+
+pub fn save_progress(
+    mut progress: ResMut<Progress>,
+    time: Res<Time>,
+    mut idle: Local<f32>,
+) {
+    match progress.is_changed() {
+        true => *idle = 0.0,
+        false => *idle += time.delta_seconds(),
+    }
+    if progress.dirty && *idle >= SAVE_DELAY {
+        write_saved_progress(&mut progress);
+    }
+}
+
+/// Writes the progress right away when the player leaves the level.
+pub fn flush_progress(
+    mut progress: ResMut<Progress>,
+) {
+    if progress.dirty {
+        write_saved_progress(&mut progress);
+    }
+}
+
+fn write_saved_progress(progress: &mut Progress) {
+    match write_progress(progress) {
+        Ok(()) => info!("Progress is saved"),
+        Err(error) => error!("Failed to save progress: {}", error),
+    }
+    progress.dirty = false;
+}
+
 pub fn init_level(
     mut next_state: ResMut<NextState<GameState>>,
-    level_config: Res<LevelConfig>,
+    mut level_config: ResMut<LevelConfig>,
     assets: Res<AssetServer>,
     mut counter: ResMut<Counter>,
 ) {
@@ -28,183 +123,581 @@ pub fn init_level(
     counter.timer.pause();
     if let Some(level_name) = level_config.level_name.clone() {
         info!("Loading level {}...", level_name);
-        let _ = assets.load_untyped(level_name);
+        level_config.handle = Some(assets.load(level_name));
     } else {
         error!("Level config is undefined");
         next_state.set(GameState::LevelsList);
     }
 }
 
+/// Drops the board of the current level and loads another one, `load_level` spawns it
+/// once the world state is gone.
+pub fn open_level(
+    mut commands: Commands,
+    mut open_events: EventReader<OpenLevel>,
+    mut level_config: ResMut<LevelConfig>,
+    assets: Res<AssetServer>,
+    entities: Query<Entity, With<Sprite>>,
+    exercises: Query<Entity, With<Exercise>>,
+) {
+    if let Some(OpenLevel(level_name)) = open_events.iter().last() {
+        info!("Opening level {}...", level_name);
+        level_config.level_name = Some(level_name.clone());
+        level_config.handle = Some(assets.load(level_name.as_str()));
+        despawn_level(&mut commands, &entities, &exercises);
+    }
+}
+
 pub fn load_level(
     mut commands: Commands,
     mut levels_events: EventReader<AssetEvent<World>>,
     levels: Res<Assets<World>>,
-    mut camera_events: EventWriter<MoveCamera>,
+    level_config: Res<LevelConfig>,
+    world: Option<Res<WorldState>>,
+    board: Query<Entity, With<Sprite>>,
+    mut images: ResMut<Assets<Image>>,
+    mut bounds: ResMut<BoardBounds>,
+    mut fit_events: EventWriter<FitBoard>,
     mut events: EventWriter<ChangeExercise>,
+    mut history: ResMut<EditHistory>,
+    mut toolbox: ResMut<Toolbox>,
+    mut stepper: ResMut<Stepper>,
+    mut recording: ResMut<Recording>,
+    mut probes: ResMut<Probes>,
+    progress: Res<Progress>,
 ) {
-    for event in levels_events.iter() {
-        match event {
-            AssetEvent::Created { handle } => {
-                if let Some(level) = levels.get(handle) {
-                    info!("Level is loaded {:?}", level);
-                    let world_state = spawn_level(level, &mut commands, &mut events);
-                    let pos = Vec2::new(
-                        CELL_SIZE * level.size.0 as f32,
-                        -CELL_SIZE * level.size.1 as f32,
-                    ) * 0.5;
-                    camera_events.send(MoveCamera {
-                        pos,
-                        force: true,
-                        absolute: true,
-                    });
-                    commands.insert_resource(world_state);
-                }
-            }
-            AssetEvent::Modified { handle } => {
-                if let Some(level) = levels.get(handle) {
-                    info!("Level is modified");
-                    let world_state = spawn_level(level, &mut commands, &mut events);
-                    let pos = Vec2::new(
-                        CELL_SIZE * level.size.0 as f32,
-                        -CELL_SIZE * level.size.1 as f32,
-                    ) * 0.5;
-                    camera_events.send(MoveCamera {
-                        pos,
-                        force: true,
-                        absolute: true,
-                    });
-                    commands.insert_resource(world_state);
+    if let Some(handle) = level_config.handle.as_ref() {
+        let modified = levels_events.iter().any(|event| match event {
+            AssetEvent::Modified { handle: modified } => modified == handle,
+            _others => false,
+        });
+        if world.is_some() && !modified {
+            return;
+        }
+
+        if let Some(level) = levels.get(handle) {
+            if modified {
+                info!("Level is modified");
+                for entity in board.iter() {
+                    commands.entity(entity).despawn_recursive();
                 }
+            } else {
+                info!("Level is loaded {:?}", level);
             }
-            _others => {}
+
+            history.clear();
+            toolbox.forget_board();
+            stepper.clear();
+            recording.clear();
+            probes.clear();
+            commands.remove_resource::<Failure>();
+            let saved = level_config.level_name.as_ref()
+                .and_then(|level_name| progress.level(level_name))
+                .and_then(|progress| progress.board.as_ref());
+            let world_state = spawn_level(level, saved, &mut commands, &mut images, &mut events);
+            bounds.0 = Some(board_rect(level.size));
+            fit_events.send(FitBoard);
+            commands.insert_resource(world_state);
         }
     }
 }
 
-pub fn find_cell_to_update(
+/// Advances the board when the timer finishes or a step is requested, and steps back to snapshots.
+pub fn tick_simulation(
     mut counter: ResMut<Counter>,
-    mut commands: Commands,
-    cells: Query<(Entity, &Cell)>,
-    updating_cells: Query<(Entity, &Cell), With<NextUpdate>>,
     time: Res<Time>,
-    world: Option<Res<WorldState>>,
+    world: Option<ResMut<WorldState>>,
+    board: Option<Res<BoardTexture>>,
+    mut images: ResMut<Assets<Image>>,
     mut exercises: Query<&mut Exercise>,
+    mut outputs: Query<(Entity, &mut ExpectedOutput)>,
+    mut stepper: ResMut<Stepper>,
+    mut recording: ResMut<Recording>,
+    mut probes: ResMut<Probes>,
 ) {
-    if let Some(world) = world {
+    if let (Some(mut world), Some(board)) = (world, board) {
         let timer = counter.timer.tick(time.delta());
-        if timer.finished() {
-            if let Ok(mut exercise) = exercises.get_single_mut() {
-                exercise.ticks += 1;
-            }
+        let mut exercise = exercises.get_single_mut().ok();
+        let ticks = exercise.as_ref()
+            .map(|exercise| exercise.ticks)
+            .unwrap_or(world.simulation.ticks());
+
+        match stepper.next_move(ticks) {
+            Move::Wait if !timer.finished() => {}
+            Move::Forward | Move::Wait => {
+                stepper.push(ticks, outputs.iter()
+                    .map(|(entity, output)| (entity, output.status.clone()))
+                    .collect());
+                if let Some(exercise) = exercise.as_mut() {
+                    if exercise.ticks == 0 {
+                        recording.start(exercise.id);
+                        probes.reset(&world.simulation);
+                    }
+                    exercise.ticks += 1;
+                }
 
-            for (id, cell) in updating_cells.iter() {
-                commands.entity(id).remove::<NextUpdate>();
-
-                let count_electron_around = world.get_cells_around(&cell.position)
-                    .iter()
-                    .map(|id| cells.get(*id).unwrap().1)
-                    .filter(|cell|
-                        cell.cell_type == ELECTRON(true) || cell.cell_type == ELECTRON(false)
-                    )
-                    .count();
-
-                match cell.cell_type.clone() {
-                    WIRE(fixed) => {
-                        if count_electron_around == 1 || count_electron_around == 2 {
-                            commands.entity(id).insert(Change(ELECTRON(fixed)));
-                        }
+                let changed = world.simulation.step();
+                stepper.record_step(&world.simulation, &changed);
+                if exercise.is_some() {
+                    recording.record_step(&world.simulation, &changed);
+                }
+                probes.sample(&world.simulation);
+                for point in changed {
+                    board.paint(&mut images, &point, world.simulation.get_cell(&point).base_color());
+                }
+            }
+            Move::Back => {
+                if let Some(snapshot) = stepper.pop() {
+                    probes.unsample();
+                    for point in restore(&mut world.simulation, &snapshot) {
+                        board.paint(&mut images, &point, world.simulation.get_cell(&point).base_color());
                     }
-                    TAIL(fixed) => {
-                        commands.entity(id).insert(Change(WIRE(fixed)));
+                    if let Some(exercise) = exercise.as_mut() {
+                        exercise.ticks = snapshot.ticks;
+                        recording.pop();
                     }
-                    ELECTRON(fixed) => {
-                        commands.entity(id).insert(Change(TAIL(fixed)));
+                    for (entity, status) in snapshot.outputs {
+                        if let Ok((_, mut output)) = outputs.get_mut(entity) {
+                            output.status = status;
+                        }
                     }
-                    _others => {}
                 }
             }
         }
     }
 }
 
-pub fn update_cells(
-    mut cells: Query<(Entity, &mut Cell, &mut Sprite, &Change), With<Change>>,
-    mut commands: Commands,
-    world: Option<Res<WorldState>>,
+/// Changes of a running exercise, like spawned electrons, can be stepped back,
+/// other changes are edits which make snapshots useless.
+pub fn apply_changes(
+    mut changes: EventReader<Change>,
+    world: Option<ResMut<WorldState>>,
+    board: Option<Res<BoardTexture>>,
+    mut images: ResMut<Assets<Image>>,
+    mut stepper: ResMut<Stepper>,
+    mut recording: ResMut<Recording>,
 ) {
-    if let Some(world) = world {
-        for (_id, mut cell, mut sprite, changed) in cells.iter_mut() {
-            let cell_type = changed.0.clone();
-            sprite.color = cell_type.clone().base_color();
-            cell.cell_type = cell_type.clone();
-
-            match cell_type {
-                ELECTRON(_) => {
-                    commands.entity(world.map[world.index(&cell.position)])
-                        .insert(NextUpdate::default());
-                    let cells_around = world.get_cells_around(&cell.position);
-
-                    for cell in cells_around.iter() {
-                        commands.entity(*cell).insert(NextUpdate::default());
-                    }
-                }
-                TAIL(_) => {
-                    commands.entity(world.map[world.index(&cell.position)])
-                        .insert(NextUpdate::default());
+    if let (Some(mut world), Some(board)) = (world, board) {
+        for Change(position, cell_type) in changes.iter() {
+            match world.lock {
+                true => {
+                    stepper.record(position, world.simulation.get_cell(position));
+                    recording.record(position, world.simulation.get_cell(position), cell_type.clone());
                 }
-                _others => {}
+                false => stepper.clear(),
+            }
+            board.paint(&mut images, position, cell_type.clone().base_color());
+            world.simulation.set_cell(position, cell_type.clone());
+        }
+    }
+}
+
+/// Remembers the board after changes of the player, changes of a running exercise aren't kept.
+pub fn keep_player_board(
+    mut changes: EventReader<Change>,
+    world: Option<Res<WorldState>>,
+    level_config: Res<LevelConfig>,
+    mut progress: ResMut<Progress>,
+) {
+    let changed = changes.iter().count() > 0;
+    if let (true, Some(world), Some(level_name)) = (changed, world, level_config.level_name.as_ref()) {
+        if !world.lock {
+            progress.keep_board(level_name, &world.simulation, world.electron_available);
+        }
+    }
+}
+
+/// Paints the tick chosen on the timeline. Running the board, stepping and edits show the live board again.
+pub fn replay_recording(
+    counter: Res<Counter>,
+    stepper: Res<Stepper>,
+    mut changes: EventReader<Change>,
+    mut recording: ResMut<Recording>,
+    world: Option<Res<WorldState>>,
+    board: Option<Res<BoardTexture>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if let (Some(world), Some(board)) = (world, board) {
+        let live = !counter.timer.paused() || stepper.forward > 0 || stepper.back > 0 || stepper.target.is_some();
+        if live || !changes.is_empty() {
+            changes.clear();
+            recording.view = None;
+        }
+
+        let tick = recording.view.unwrap_or(recording.len());
+        if tick == recording.shown() {
+            return;
+        }
+        for (point, cell_type) in recording.seek(tick) {
+            let cell_type = match recording.view {
+                Some(_) => cell_type,
+                None => world.simulation.get_cell(&point),
             };
+            board.paint(&mut images, &point, cell_type.base_color());
+        }
+    }
+}
+
+/// Applies player edits through `Change` events and remembers them in the history.
+pub fn apply_edits(
+    mut edits: EventReader<Edit>,
+    world: Option<Res<WorldState>>,
+    mut history: ResMut<EditHistory>,
+    mut changes: EventWriter<Change>,
+) {
+    if let Some(world) = world {
+        for Edit(cells) in edits.iter() {
+            if world.lock { continue; }
+
+            let step = cell_edits(cells, |point| world.simulation.get_cell(point));
+            for edit in step.iter() {
+                changes.send(Change(edit.point.clone(), edit.after.clone()));
+            }
+            history.record(step);
         }
     }
 }
 
 pub fn handle_clicks(
-    mut commands: Commands,
+    mut edits: EventWriter<Edit>,
     mut click_events: EventReader<ClickEvent>,
     world: Option<Res<WorldState>>,
-    cells: Query<&mut Cell>,
+    mut toolbox: ResMut<Toolbox>,
+    mut probes: ResMut<Probes>,
+    interactions: Query<&Interaction>,
 ) {
     if let Some(world) = world {
-        let half_cell_size = CELL_SIZE / 2.;
-
         for event in click_events.iter() {
+            if interactions.iter().any(|interaction| *interaction != Interaction::None) {
+                continue;
+            }
+            let Some(point) = cell_at(event.pos, world.size) else {
+                continue;
+            };
+
+            // Probes don't change the board, so they are attached while an exercise is running too.
+            if event.button == MouseButton::Left && toolbox.tool == Tool::Probe && !toolbox.pasting {
+                match probes.toggle(&point, &world.simulation) {
+                    Some(name) => info!("Probe {} at {:?}", name, point),
+                    None => info!("Probe at {:?} is removed", point),
+                }
+                continue;
+            }
             if world.lock { continue; }
 
-            let x = (event.pos.x + half_cell_size) / CELL_SIZE;
-            let y = (-event.pos.y + half_cell_size) / CELL_SIZE;
+            if event.button == MouseButton::Left {
+                if let (true, Some(pattern)) = (toolbox.pasting, toolbox.clipboard.as_ref()) {
+                    edits.send(Edit(pattern.place(&point, &world.simulation, world.electron_available)));
+                    continue;
+                }
+                if toolbox.tool == Tool::Select {
+                    toolbox.selection = None;
+                    continue;
+                }
+            }
 
-            if x < 0. || x >= world.size.0 as f32 || y < 0. || y >= world.size.1 as f32 {
+            let cell_type = world.simulation.get_cell(&point);
+            edits.send(Edit(vec![(
+                point,
+                match event.button {
+                    MouseButton::Left if toolbox.tool != Tool::Single => toolbox.tool.paint(&cell_type)
+                        .unwrap_or(cell_type),
+                    MouseButton::Left => match cell_type {
+                        WIRE(false) => EMPTY(false),
+                        EMPTY(false) => WIRE(false),
+                        other => other
+                    },
+                    _others => match (cell_type, world.electron_available) {
+                        (WIRE(fixed), true) => ELECTRON(fixed),
+                        other => other.0
+                    },
+                },
+            )]));
+        }
+    }
+}
+
+/// Collects cells under a left button drag and sends them as one edit when the button is released,
+/// the select tool keeps the dragged rectangle as the selection instead.
+pub fn handle_strokes(
+    mut edits: EventWriter<Edit>,
+    mut drag_events: EventReader<DragEvent>,
+    world: Option<Res<WorldState>>,
+    mut toolbox: ResMut<Toolbox>,
+    interactions: Query<&Interaction>,
+) {
+    if let Some(world) = world {
+        for event in drag_events.iter() {
+            if world.lock || toolbox.pasting || toolbox.tool == Tool::Single || toolbox.tool == Tool::Probe {
+                toolbox.stroke = None;
                 continue;
             }
 
-            let x = x.trunc() as usize;
-            let y = y.trunc() as usize;
-            let cell = world.get_cell(&Point(x, y));
-            if let Ok(cell_type) = cells.get(cell) {
-                commands.entity(cell).insert(Change(
-                    match event.button {
-                        MouseButton::Left => match cell_type.cell_type.clone() {
-                            WIRE(false) => EMPTY(false),
-                            EMPTY(false) => WIRE(false),
-                            other => other
-                        },
-                        _others => match (cell_type.cell_type.clone(), world.electron_available) {
-                            (WIRE(fixed), true) => ELECTRON(fixed),
-                            other => other.0
-                        },
-                    })
-                );
+            let point = cell_at(event.pos, world.size);
+            let over_ui = interactions.iter().any(|interaction| *interaction != Interaction::None);
+            match (event.phase, point) {
+                (DragPhase::Start, Some(_)) if over_ui => {}
+                (DragPhase::Start, Some(point)) => toolbox.stroke = Some(Stroke::new(point)),
+                (DragPhase::Move, Some(point)) => {
+                    if let Some(stroke) = toolbox.stroke.as_mut() {
+                        stroke.extend(point);
+                    }
+                }
+                (DragPhase::End, _) if toolbox.tool == Tool::Select => {
+                    if let Some(stroke) = toolbox.stroke.take() {
+                        toolbox.selection = Some((stroke.start, stroke.end));
+                    }
+                }
+                (DragPhase::End, _) => {
+                    if let Some(stroke) = toolbox.stroke.take() {
+                        let cells = stroke.cells(toolbox.tool).into_iter()
+                            .filter_map(|point| {
+                                let cell_type = toolbox.tool.paint(&world.simulation.get_cell(&point))?;
+                                Some((point, cell_type))
+                            })
+                            .collect();
+                        edits.send(Edit(cells));
+                    }
+                }
+                _others => {}
             }
         }
     }
 }
 
-pub fn spawn_electron(
+/// Shows cells of the current stroke before they are painted, the selected region
+/// and the clipboard under the cursor while pasting.
+pub fn draw_tool_preview(
+    mut commands: Commands,
+    toolbox: Res<Toolbox>,
+    cursor: Res<CursorPosition>,
+    world: Option<Res<WorldState>>,
+    previews: Query<Entity, With<StrokePreview>>,
+    mut pasted_at: Local<Option<Point>>,
+) {
+    let Some(world) = world else {
+        return;
+    };
+    let cursor_cell = cursor.0
+        .filter(|_| toolbox.pasting)
+        .and_then(|pos| cell_at(pos, world.size));
+    if !toolbox.is_changed() && cursor_cell == *pasted_at {
+        return;
+    }
+    *pasted_at = cursor_cell.clone();
+
+    for entity in previews.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let mut cells = Vec::new();
+    match (toolbox.stroke.as_ref(), toolbox.selection.as_ref()) {
+        (Some(stroke), _) if toolbox.tool == Tool::Select => {
+            cells.extend(stroke.cells(Tool::Select).into_iter().map(|point| (point, SELECTION_COLOR)));
+        }
+        (Some(stroke), _) => {
+            cells.extend(stroke.cells(toolbox.tool).into_iter()
+                .filter_map(|point| {
+                    let cell_type = toolbox.tool.paint(&world.simulation.get_cell(&point))?;
+                    Some((point, cell_type.base_color()))
+                }));
+        }
+        (None, Some((from, to))) => {
+            cells.extend(rect(from, to).into_iter().map(|point| (point, SELECTION_COLOR)));
+        }
+        (None, None) => {}
+    }
+    if let (Some(point), Some(pattern)) = (cursor_cell, toolbox.clipboard.as_ref()) {
+        cells.extend(pattern.place(&point, &world.simulation, world.electron_available).into_iter()
+            .map(|(point, cell_type)| (point, cell_type.base_color())));
+    }
+
+    for (point, color) in cells {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: color.with_a(0.7),
+                    custom_size: Some(Vec2::splat(CELL_SIZE * 0.8)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::from((cell_center(&point), 1.))),
+                ..default()
+            },
+            StrokePreview::default(),
+        ));
+    }
+}
+
+/// Marks probed cells on the board with the names of the probes.
+pub fn draw_probe_marks(
     mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    probes: Res<Probes>,
+    marks: Query<Entity, With<ProbeMark>>,
+    world: Option<Res<WorldState>>,
+    mut revision: Local<Option<usize>>,
+) {
+    if world.is_none() || *revision == Some(probes.revision) {
+        return;
+    }
+    *revision = Some(probes.revision);
+
+    for entity in marks.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: CELL_SIZE * 0.4,
+        color: Color::WHITE,
+    };
+    for probe in probes.probes.iter() {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: PROBE_COLOR.with_a(0.5),
+                    custom_size: Some(Vec2::splat(CELL_SIZE * 0.4)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::from((cell_center(&probe.position), 1.))),
+                ..default()
+            },
+            ProbeMark::default(),
+        )).with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(probe.name.clone(), text_style.clone()),
+                text_anchor: BottomCenter,
+                transform: Transform::from_translation(Vec3::new(0.0, CELL_SIZE * 0.5, 1.0)),
+                ..default()
+            });
+        });
+    }
+}
+
+/// Highlights the cell of the last failure while the explanation is shown.
+pub fn draw_failure_mark(
+    mut commands: Commands,
+    failure: Option<Res<Failure>>,
+    marks: Query<Entity, With<FailureMark>>,
+) {
+    let changed = failure.as_ref().map(|failure| failure.is_changed()).unwrap_or(!marks.is_empty());
+    if !changed {
+        return;
+    }
+
+    for entity in marks.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some(position) = failure.and_then(|failure| failure.position.clone()) {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: FAILURE_COLOR.with_a(0.6),
+                    custom_size: Some(Vec2::splat(CELL_SIZE * 1.4)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::from((cell_center(&position), 0.5))),
+                ..default()
+            },
+            FailureMark::default(),
+        ));
+    }
+}
+
+/// Bound keys move the cell cursor, the first press puts it at the center of the window.
+/// Other keys click the cell under it with the left or the right button.
+pub fn keyboard_cursor(
+    keys: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    world: Option<Res<WorldState>>,
+    stepper: Res<Stepper>,
+    mut toolbox: ResMut<Toolbox>,
+    windows: Query<&Window>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut click_events: EventWriter<ClickEvent>,
+    mut camera_events: EventWriter<MoveCamera>,
+) {
+    let (Some(world), Ok((transform, projection))) = (world, camera.get_single()) else {
+        return;
+    };
+    if stepper.input.is_some() {
+        return;
+    }
+
+    let moves = [
+        (KeyAction::CursorLeft, (-1, 0)),
+        (KeyAction::CursorRight, (1, 0)),
+        (KeyAction::CursorUp, (0, -1)),
+        (KeyAction::CursorDown, (0, 1)),
+    ];
+    for (action, (dx, dy)) in moves {
+        if !bindings.just_pressed(&keys, action) {
+            continue;
+        }
+        let moved = match toolbox.cursor.clone() {
+            Some(Point(x, y)) => Point(
+                (x as isize + dx).clamp(0, world.size.0 as isize - 1) as usize,
+                (y as isize + dy).clamp(0, world.size.1 as isize - 1) as usize,
+            ),
+            None => cell_at(transform.translation.truncate(), world.size).unwrap_or(Point(0, 0)),
+        };
+
+        let window = windows.single();
+        let view = view_rect(transform.translation.truncate(), projection.scale, Vec2::new(window.width(), window.height()));
+        if !view.contains(cell_center(&moved)) {
+            camera_events.send(MoveCamera {
+                pos: cell_center(&moved),
+                force: true,
+                absolute: true,
+            });
+        }
+        toolbox.cursor = Some(moved);
+    }
+
+    if let Some(cursor) = toolbox.cursor.clone() {
+        for (action, button) in [(KeyAction::ToggleCell, MouseButton::Left), (KeyAction::PlaceElectron, MouseButton::Right)] {
+            if bindings.just_pressed(&keys, action) {
+                click_events.send(ClickEvent {
+                    pos: cell_center(&cursor),
+                    button,
+                });
+            }
+        }
+    }
+}
+
+pub fn draw_keyboard_cursor(
+    mut commands: Commands,
+    toolbox: Res<Toolbox>,
+    marks: Query<Entity, With<CursorMark>>,
+) {
+    if !toolbox.is_changed() {
+        return;
+    }
+
+    for entity in marks.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some(cursor) = toolbox.cursor.as_ref() {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: CURSOR_COLOR.with_a(0.4),
+                    custom_size: Some(Vec2::splat(CELL_SIZE * 1.2)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::from((cell_center(cursor), 0.6))),
+                ..default()
+            },
+            CursorMark::default(),
+        ));
+    }
+}
+
+pub fn spawn_electron(
+    mut changes: EventWriter<Change>,
     exercises: Query<&Exercise, Changed<Exercise>>,
     spawns: Query<&ElectronSpawn>,
     world: Option<Res<WorldState>>,
-    cells: Query<&mut Cell>,
 ) {
     if let Some(world) = world {
         if let Ok(exercise) = exercises.get_single() {
@@ -213,10 +706,8 @@ pub fn spawn_electron(
             for spawn in spawns.iter() {
                 info!("spawn is excepting {}", spawn.instant);
                 if spawn.instant == exercise.ticks {
-                    let cell = world.get_cell(&spawn.position);
-                    if let Ok(cell_type) = cells.get(cell) {
-                        commands.entity(cell).insert(Change(ELECTRON(cell_type.is_fixed())));
-                    }
+                    let fixed = world.simulation.get_cell(&spawn.position).is_fixed();
+                    changes.send(Change(spawn.position.clone(), ELECTRON(fixed)));
                 }
             }
         }
@@ -227,34 +718,12 @@ pub fn handle_outputs(
     mut outputs: Query<&mut ExpectedOutput>,
     exercises: Query<&Exercise, Changed<Exercise>>,
     world: Option<Res<WorldState>>,
-    cells: Query<&mut Cell>,
 ) {
     if let Some(world) = world {
         if let Ok(exercise) = exercises.get_single() {
             for mut output in outputs.iter_mut() {
-                if exercise.ticks < output.from {
-                    output.status = Inactive;
-                } else if exercise.ticks >= output.from && exercise.ticks < output.until {
-                    if output.status == Inactive {
-                        output.status = Waiting;
-                    }
-                    let cell = world.get_cell(&output.position);
-                    if let Ok(cell) = cells.get(cell) {
-                        if let ELECTRON(_) = cell.cell_type {
-                            output.status = match output.expectation {
-                                true => Success,
-                                false => Fail
-                            }
-                        }
-                    }
-                } else if exercise.ticks >= output.until {
-                    if !output.expectation && output.status != Fail {
-                        output.status = Success;
-                    }
-                    if output.expectation && output.status != Success {
-                        output.status = Fail;
-                    }
-                }
+                let cell = world.simulation.get_cell(&output.position);
+                output.update(exercise.ticks, &cell);
             }
         }
     }
@@ -269,6 +738,9 @@ pub fn handle_exercises(
     camera: Query<Entity, &Camera2d>,
     world: Option<ResMut<WorldState>>,
     mut level_state: ResMut<NextState<LevelState>>,
+    level_config: Res<LevelConfig>,
+    mut progress: ResMut<Progress>,
+    mut scoring: ResMut<Scoring>,
 ) {
     if let Ok((exercise_id, exercise)) = exercises.get_single() {
         if let Some(mut world) = world {
@@ -278,7 +750,16 @@ pub fn handle_exercises(
                 .map(|output| output.status.clone()).collect();
             info!("statues: {:?}", statues);
             if outputs.iter().any(|o| o.status == Fail) || exercise.ticks > exercise.timeout {
-                info!("Fail exercise");
+                // Outputs are listed in the order of the exercise, so their numbers match the level.
+                let ordered: Vec<&ExpectedOutput> = world.exercises.get(exercise.id)
+                    .map(|data| data.outputs.iter()
+                        .filter_map(|(position, _, from, _)| outputs.iter()
+                            .find(|output| output.position == *position && output.from == *from))
+                        .collect())
+                    .unwrap_or_default();
+                let failure = Failure::explain(exercise, &ordered);
+                info!("Fail exercise: {}", failure.message());
+                commands.insert_resource(failure);
                 commands.entity(exercise_id).despawn_recursive();
                 commands.entity(camera)
                     .insert(blink_background(
@@ -293,6 +774,7 @@ pub fn handle_exercises(
 
             if outputs.iter().all(|o| o.status == Success) {
                 info!("Success exercise");
+                scoring.ticks += exercise.ticks;
                 commands.entity(exercise_id).despawn_recursive();
                 commands.entity(camera)
                     .insert(blink_background(
@@ -303,6 +785,9 @@ pub fn handle_exercises(
                 if exercise.id + 1 < world.exercises.len() {
                     events.send(ChangeExercise(exercise.id + 1));
                 } else {
+                    if let Some(level_name) = level_config.level_name.as_ref() {
+                        progress.solve(level_name);
+                    }
                     level_state.set(LevelState::Finish);
                 }
                 world.lock = false;
@@ -311,26 +796,46 @@ pub fn handle_exercises(
     }
 }
 
+/// Scores the solved level for the finish screen and updates personal bests.
+pub fn score_level(
+    world: Option<Res<WorldState>>,
+    levels: Res<Assets<World>>,
+    level_config: Res<LevelConfig>,
+    mut progress: ResMut<Progress>,
+    mut scoring: ResMut<Scoring>,
+) {
+    let (Some(world), Some(handle), Some(level_name)) = (world, level_config.handle.as_ref(), level_config.level_name.as_ref()) else {
+        return;
+    };
+    let Some(level) = levels.get(handle) else {
+        return;
+    };
+
+    let score = Score::measure(&Simulation::new(level), &world.simulation, scoring.ticks);
+    info!("Level {} is solved with {:?}", level_name, score);
+    scoring.best = progress.record_score(level_name, &score);
+    scoring.score = Some(score);
+}
+
 pub fn outputs_indication(
     outputs: Query<&ExpectedOutput>,
     world: Option<Res<WorldState>>,
-    mut cells: Query<(&mut Cell, &mut Sprite)>,
+    board: Option<Res<BoardTexture>>,
+    mut images: ResMut<Assets<Image>>,
     mut time: Local<i32>,
     exercises: Query<&Exercise>,
 ) {
-    if let Some(world) = world {
+    if let (Some(world), Some(board)) = (world, board) {
         if let Ok(exercise) = exercises.get_single() {
             *time = (*time + 1) % 30;
             for output in outputs.iter() {
                 if exercise.ticks >= output.from && exercise.ticks < output.until {
-                    let cell = world.get_cell(&output.position);
-                    if let Ok((cell, mut sprite)) = cells.get_mut(cell) {
-                        sprite.color = if *time < 15 {
-                            cell.cell_type.clone().base_color()
-                        } else {
-                            Color::MIDNIGHT_BLUE
-                        }
-                    }
+                    let color = if *time < 15 {
+                        world.simulation.get_cell(&output.position).base_color()
+                    } else {
+                        Color::MIDNIGHT_BLUE
+                    };
+                    board.paint(&mut images, &output.position, color);
                 }
             }
         }
@@ -343,10 +848,17 @@ pub fn change_exercise(
     asset_server: Res<AssetServer>,
     world: Option<Res<WorldState>>,
     exercises: Query<Entity, With<Exercise>>,
+    mut stepper: ResMut<Stepper>,
+    mut probes: ResMut<Probes>,
+    mut scoring: ResMut<Scoring>,
 ) {
     if let Some(world) = world {
         if let Some(ChangeExercise(exercise_id)) = events.iter().next() {
             info!("Spawn exercise {}", exercise_id);
+            stepper.clear();
+            if *exercise_id == 0 {
+                scoring.ticks = 0;
+            }
             for exercise in exercises.iter() {
                 commands.entity(exercise).despawn_recursive();
             }
@@ -356,6 +868,9 @@ pub fn change_exercise(
                 world.size.1 as f32 * CELL_SIZE,
             );
             let exercise = world.exercises.get(*exercise_id).unwrap();
+            let outputs: Vec<Point> = exercise.outputs.iter().map(|output| output.0.clone()).collect();
+            probes.set_outputs(&outputs, &world.simulation);
+            probes.reset(&world.simulation);
 
             let mut exercise_entity = commands.spawn(
                 (
@@ -399,83 +914,48 @@ pub fn destroy_level(
     mut commands: Commands,
     entities: Query<Entity, With<Sprite>>,
     exercises: Query<Entity, With<Exercise>>,
+) {
+    despawn_level(&mut commands, &entities, &exercises);
+}
+
+fn despawn_level(
+    commands: &mut Commands,
+    entities: &Query<Entity, With<Sprite>>,
+    exercises: &Query<Entity, With<Exercise>>,
 ) {
     commands.remove_resource::<WorldState>();
+    commands.remove_resource::<BoardTexture>();
+    commands.remove_resource::<Failure>();
     for entity in entities.iter() {
         commands.entity(entity).despawn_recursive();
     }
     for entity in exercises.iter() {
         commands.entity(entity).despawn_recursive();
     }
-    commands.remove_resource::<WorldState>();
 }
 
+/// Spawns the board of the level with the last board of the player on it.
 fn spawn_level(
     world: &World,
+    saved: Option<&Vec<String>>,
     commands: &mut Commands,
+    images: &mut Assets<Image>,
     events: &mut EventWriter<ChangeExercise>,
 ) -> WorldState {
     let mut world_state = WorldState {
         size: world.size,
-        map: Vec::with_capacity(world.size.0 * world.size.1),
+        simulation: Simulation::new(world),
         exercises: world.exercises.clone(),
         electron_available: world.electron_available,
         lock: false,
     };
-
-    for y in 0..world.size.1 {
-        for x in 0..world.size.0 {
-            let pos = Point(x, y);
-            let cell_type = world.get_cell(&pos);
-            let cell = Cell {
-                position: pos,
-                cell_type: cell_type.clone(),
-            };
-
-            let entity = commands.spawn((
-                cell,
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: cell_type.base_color(),
-                        custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
-                        ..default()
-                    },
-                    transform: Transform::from_translation(
-                        Vec3::new(
-                            CELL_SIZE.mul(x as f32),
-                            -CELL_SIZE.mul(y as f32),
-                            0.,
-                        )
-                    ),
-                    ..default()
-                },
-            )).id();
-
-            world_state.map.push(entity);
-        }
+    if let Some(saved) = saved {
+        let restored = restore_board(&mut world_state.simulation, saved);
+        info!("Restored {} cells of the last board", restored.len());
     }
 
-    for x in 0..world.size.0 {
-        for y in 0..world.size.1 {
-            let point = Point(x, y);
-            match world.get_cell(&point).clone() {
-                ELECTRON(_) => {
-                    commands.entity(world_state.map[world.index(&point)])
-                        .insert(NextUpdate::default());
-                    let cells_around = world_state.get_cells_around(&point);
-
-                    for cell in cells_around.iter() {
-                        commands.entity(*cell).insert(NextUpdate::default());
-                    }
-                }
-                TAIL(_) => {
-                    commands.entity(world_state.map[world.index(&point)])
-                        .insert(NextUpdate::default());
-                }
-                _others => {}
-            }
-        }
-    }
+    let board = BoardTexture::spawn(&world_state.simulation, commands, images);
+    commands.insert_resource(board);
 
     if world.exercises.len() > 0 {
         events.send(ChangeExercise(0));
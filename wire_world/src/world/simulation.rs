@@ -0,0 +1,311 @@
+use crate::world::components::{Boundary, CellType, Point};
+use crate::world::grid::{ELECTRON_STATE, encode, Grid, STATE_MASK, TAIL_STATE, WIRE_STATE};
+use crate::world::resources::World;
+
+/// Wire-World automaton which doesn't depend on Bevy ECS.
+/// It keeps the whole board and the list of cells which can change on the next generation.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    grid: Grid,
+    boundary: Boundary,
+    active: Vec<u32>,
+    queued: Vec<bool>,
+    ticks: usize,
+}
+
+impl Simulation {
+    pub fn new(world: &World) -> Simulation {
+        let mut simulation = Simulation {
+            grid: Grid::new(world.size, &world.map),
+            boundary: world.boundary,
+            active: Vec::new(),
+            queued: vec![false; world.map.len()],
+            ticks: 0,
+        };
+
+        for index in 0..simulation.grid.len() {
+            simulation.wake(index);
+        }
+
+        simulation
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.grid.size()
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    pub fn index(&self, point: &Point) -> usize {
+        point.1 * self.size().0 + point.0
+    }
+
+    pub fn point(&self, index: usize) -> Point {
+        Point(index % self.size().0, index / self.size().0)
+    }
+
+    pub fn get_cell(&self, point: &Point) -> CellType {
+        self.grid.get_cell(self.index(point))
+    }
+
+    /// Replaces a cell and wakes it up together with its neighbours.
+    pub fn set_cell(&mut self, point: &Point, cell_type: CellType) {
+        let index = self.index(point);
+        self.grid.set(index, encode(&cell_type));
+        self.enqueue(index);
+        for neighbour in self.neighbours(index).into_iter().flatten() {
+            self.enqueue(neighbour);
+        }
+    }
+
+    pub fn cells_around(&self, point: &Point) -> Vec<Point> {
+        self.neighbours(self.index(point))
+            .into_iter()
+            .flatten()
+            .map(|index| self.point(index))
+            .collect()
+    }
+
+    /// Advances the board by one generation and returns the cells which were changed.
+    pub fn step(&mut self) -> Vec<Point> {
+        let active = std::mem::take(&mut self.active);
+        let mut changes: Vec<u32> = Vec::new();
+
+        for index in active.iter() {
+            let index = *index as usize;
+            self.queued[index] = false;
+
+            let cell = self.grid.get(index);
+            let next = match cell & STATE_MASK {
+                WIRE_STATE => {
+                    let count_electron_around = self.neighbours(index)
+                        .into_iter()
+                        .flatten()
+                        .filter(|neighbour| self.grid.get(*neighbour) & STATE_MASK == ELECTRON_STATE)
+                        .count();
+
+                    if count_electron_around == 1 || count_electron_around == 2 {
+                        cell ^ WIRE_STATE ^ ELECTRON_STATE
+                    } else {
+                        cell
+                    }
+                }
+                ELECTRON_STATE => cell ^ ELECTRON_STATE ^ TAIL_STATE,
+                TAIL_STATE => cell ^ TAIL_STATE ^ WIRE_STATE,
+                _others => cell,
+            };
+
+            if next != cell {
+                self.grid.set_next(index, next);
+                changes.push(index as u32);
+            }
+        }
+
+        self.grid.swap(&changes);
+        for index in changes.iter() {
+            self.wake(*index as usize);
+        }
+        self.ticks += 1;
+
+        changes.into_iter()
+            .map(|index| self.point(index as usize))
+            .collect()
+    }
+
+    /// Moves the generation counter back after cells of the previous generation are restored.
+    pub fn rewind(&mut self) {
+        self.ticks = self.ticks.saturating_sub(1);
+    }
+
+    /// Neighbours of a cell, `None` stands for a cell beyond a dead edge.
+    fn neighbours(&self, index: usize) -> [Option<usize>; 8] {
+        let (width, height) = self.size();
+        let (x, y) = (index % width, index / width);
+        let left = shift(x, -1, width, self.boundary.wraps_x());
+        let right = shift(x, 1, width, self.boundary.wraps_x());
+        let up = shift(y, -1, height, self.boundary.wraps_y());
+        let down = shift(y, 1, height, self.boundary.wraps_y());
+        let cell = |x: Option<usize>, y: Option<usize>| Some(y? * width + x?);
+
+        [
+            cell(left, up), cell(left, Some(y)), cell(left, down),
+            cell(Some(x), up), cell(Some(x), down),
+            cell(right, up), cell(right, Some(y)), cell(right, down),
+        ]
+    }
+
+    fn wake(&mut self, index: usize) {
+        match self.grid.get(index) & STATE_MASK {
+            ELECTRON_STATE => {
+                self.enqueue(index);
+                for neighbour in self.neighbours(index).into_iter().flatten() {
+                    self.enqueue(neighbour);
+                }
+            }
+            TAIL_STATE => {
+                self.enqueue(index);
+            }
+            _others => {}
+        }
+    }
+
+    fn enqueue(&mut self, index: usize) {
+        if !self.queued[index] {
+            self.queued[index] = true;
+            self.active.push(index as u32);
+        }
+    }
+}
+
+fn shift(value: usize, delta: isize, size: usize, wrap: bool) -> Option<usize> {
+    let shifted = value as isize + delta;
+    if shifted >= 0 && shifted < size as isize {
+        Some(shifted as usize)
+    } else if wrap {
+        Some(shifted.rem_euclid(size as isize) as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::CellType::{ELECTRON, EMPTY, TAIL, WIRE};
+
+    fn simulation(level: &str) -> Simulation {
+        Simulation::new(&World::from_string(level.to_owned()).unwrap())
+    }
+
+    #[test]
+    fn electron_moves_along_wire() {
+        let mut simulation = simulation("5 3\ntrue\ne e e e e\ne A w w e\ne e e e e\n0\n");
+
+        simulation.step();
+        assert_eq!(simulation.get_cell(&Point(1, 1)), TAIL(true));
+        assert_eq!(simulation.get_cell(&Point(2, 1)), ELECTRON(false));
+        assert_eq!(simulation.get_cell(&Point(3, 1)), WIRE(false));
+
+        simulation.step();
+        assert_eq!(simulation.get_cell(&Point(1, 1)), WIRE(true));
+        assert_eq!(simulation.get_cell(&Point(2, 1)), TAIL(false));
+        assert_eq!(simulation.get_cell(&Point(3, 1)), ELECTRON(false));
+        assert_eq!(simulation.ticks(), 2);
+    }
+
+    #[test]
+    fn step_returns_only_changed_cells() {
+        let mut simulation = simulation("5 3\ntrue\ne e e e e\ne a w e e\ne e e e e\n0\n");
+
+        let mut changed = simulation.step();
+        changed.sort_by_key(|point| (point.1, point.0));
+        assert_eq!(changed, vec![Point(1, 1), Point(2, 1)]);
+    }
+
+    #[test]
+    fn wire_with_three_electrons_around_stays_wire() {
+        let mut simulation = simulation("5 5\ntrue\ne e e e e\ne a a a e\ne e w e e\ne e e e e\ne e e e e\n0\n");
+
+        simulation.step();
+        assert_eq!(simulation.get_cell(&Point(2, 2)), WIRE(false));
+    }
+
+    #[test]
+    fn set_cell_wakes_board() {
+        let mut simulation = simulation("5 3\ntrue\ne e e e e\ne w w e e\ne e e e e\n0\n");
+
+        assert!(simulation.step().is_empty());
+        simulation.set_cell(&Point(1, 1), ELECTRON(false));
+        simulation.step();
+        assert_eq!(simulation.get_cell(&Point(2, 1)), ELECTRON(false));
+        assert_eq!(simulation.get_cell(&Point(0, 0)), EMPTY(false));
+    }
+
+    #[test]
+    fn large_loop_keeps_only_electrons_active() {
+        let size = 1000;
+        let mut map = vec![EMPTY(false); size * size];
+        for cell in map.iter_mut().take(size) {
+            *cell = WIRE(false);
+        }
+        map[0] = ELECTRON(false);
+        map[size - 1] = TAIL(false);
+        let world = World {
+            title: String::new(),
+            size: (size, size),
+            boundary: Boundary::Wrap,
+            map,
+            electron_available: true,
+            exercises: vec![],
+        };
+        let mut simulation = Simulation::new(&world);
+
+        for _ in 0..size {
+            assert_eq!(simulation.step().len(), 3);
+        }
+        assert_eq!(simulation.get_cell(&Point(0, 0)), ELECTRON(false));
+    }
+
+    const EDGES: &str = "true\nw e e w\ne e e e\ne e e e\nw e e a\n0\n";
+
+    fn edges(boundary: &str) -> Simulation {
+        let mut simulation = simulation(&format!("4 4 {boundary}\n{EDGES}"));
+        simulation.step();
+        simulation
+    }
+
+    #[test]
+    fn wrap_connects_both_axes() {
+        let simulation = edges("wrap");
+        assert_eq!(simulation.boundary(), Boundary::Wrap);
+        assert_eq!(simulation.get_cell(&Point(0, 3)), ELECTRON(false));
+        assert_eq!(simulation.get_cell(&Point(3, 0)), ELECTRON(false));
+        assert_eq!(simulation.get_cell(&Point(0, 0)), ELECTRON(false));
+    }
+
+    #[test]
+    fn wrap_is_default_boundary() {
+        let mut simulation = simulation(&format!("4 4\n{EDGES}"));
+        simulation.step();
+        assert_eq!(simulation.boundary(), Boundary::Wrap);
+        assert_eq!(simulation.get_cell(&Point(0, 0)), ELECTRON(false));
+    }
+
+    #[test]
+    fn dead_border_isolates_edges() {
+        let simulation = edges("dead");
+        assert_eq!(simulation.get_cell(&Point(0, 3)), WIRE(false));
+        assert_eq!(simulation.get_cell(&Point(3, 0)), WIRE(false));
+        assert_eq!(simulation.get_cell(&Point(0, 0)), WIRE(false));
+        assert_eq!(simulation.cells_around(&Point(0, 0)).len(), 3);
+    }
+
+    #[test]
+    fn wrap_x_connects_only_left_and_right() {
+        let simulation = edges("wrap-x");
+        assert_eq!(simulation.get_cell(&Point(0, 3)), ELECTRON(false));
+        assert_eq!(simulation.get_cell(&Point(3, 0)), WIRE(false));
+        assert_eq!(simulation.get_cell(&Point(0, 0)), WIRE(false));
+        assert_eq!(simulation.cells_around(&Point(0, 0)).len(), 5);
+    }
+
+    #[test]
+    fn wrap_y_connects_only_top_and_bottom() {
+        let simulation = edges("wrap-y");
+        assert_eq!(simulation.get_cell(&Point(0, 3)), WIRE(false));
+        assert_eq!(simulation.get_cell(&Point(3, 0)), ELECTRON(false));
+        assert_eq!(simulation.get_cell(&Point(0, 0)), WIRE(false));
+        assert_eq!(simulation.cells_around(&Point(0, 0)).len(), 5);
+    }
+
+    #[test]
+    fn unknown_boundary_is_rejected() {
+        assert!(World::from_string(format!("4 4 sphere\n{EDGES}")).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::world::components::{CellType, OutputStatus, Point};
+use crate::world::components::CellType::{ELECTRON, TAIL, WIRE};
+use crate::world::simulation::Simulation;
+
+/// Count of generations which can be stepped back, older ones are forgotten.
+pub const SNAPSHOT_LIMIT: usize = 1000;
+
+/// What is needed to return to the generation before a tick: the exercise tick, statuses of outputs
+/// and previous states of every cell changed by the tick and by spawns after it.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub ticks: usize,
+    pub outputs: Vec<(Entity, OutputStatus)>,
+    pub cells: Vec<(Point, CellType)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Forward,
+    Back,
+    Wait,
+}
+
+/// Manual simulation control: single steps, steps back and running to a tick.
+#[derive(Resource, Debug, Default)]
+pub struct Stepper {
+    snapshots: VecDeque<Snapshot>,
+    /// Steps requested by the step button, they don't wait for the timer.
+    pub forward: usize,
+    pub back: usize,
+    /// Exercise tick to run forward or back to, one generation per frame.
+    pub target: Option<usize>,
+    /// Digits typed into the run to tick field, `None` while the field isn't focused.
+    pub input: Option<String>,
+}
+
+impl Stepper {
+    /// Remembers the generation before a tick, the snapshot gets cells changed by the tick later.
+    pub fn push(&mut self, ticks: usize, outputs: Vec<(Entity, OutputStatus)>) {
+        self.snapshots.push_back(Snapshot { ticks, outputs, cells: Vec::new() });
+        if self.snapshots.len() > SNAPSHOT_LIMIT {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Remembers the previous state of a cell changed since the last snapshot.
+    pub fn record(&mut self, point: &Point, before: CellType) {
+        if let Some(snapshot) = self.snapshots.back_mut() {
+            if snapshot.cells.iter().all(|(recorded, _)| recorded != point) {
+                snapshot.cells.push((point.clone(), before));
+            }
+        }
+    }
+
+    /// Remembers cells changed by the generation right after `push`, their previous states follow
+    /// from the rules. Cells of one generation are distinct, so they aren't looked up like in `record`.
+    pub fn record_step(&mut self, simulation: &Simulation, changed: &[Point]) {
+        if let Some(snapshot) = self.snapshots.back_mut() {
+            snapshot.cells.extend(changed.iter().map(|point| {
+                let before = match simulation.get_cell(point) {
+                    ELECTRON(fixed) => WIRE(fixed),
+                    TAIL(fixed) => ELECTRON(fixed),
+                    WIRE(fixed) => TAIL(fixed),
+                    other => other,
+                };
+                (point.clone(), before)
+            }));
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn can_step_back(&self) -> bool {
+        !self.snapshots.is_empty()
+    }
+
+    /// Forgets snapshots and requested moves, for example when the board is edited or an exercise is changed.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.forward = 0;
+        self.back = 0;
+        self.target = None;
+    }
+
+    /// Move requested for the current frame at the given exercise tick.
+    pub fn next_move(&mut self, ticks: usize) -> Move {
+        if self.back > 0 {
+            self.back -= 1;
+            return match self.can_step_back() {
+                true => Move::Back,
+                false => Move::Wait,
+            };
+        }
+        if self.forward > 0 {
+            self.forward -= 1;
+            return Move::Forward;
+        }
+
+        match self.target {
+            Some(target) if target > ticks => Move::Forward,
+            Some(target) if target < ticks && self.can_step_back() => Move::Back,
+            Some(_) => {
+                self.target = None;
+                Move::Wait
+            }
+            None => Move::Wait,
+        }
+    }
+}
+
+/// Reverts the simulation to the generation of the snapshot and returns the changed cells.
+pub fn restore(simulation: &mut Simulation, snapshot: &Snapshot) -> Vec<Point> {
+    simulation.rewind();
+    snapshot.cells.iter()
+        .map(|(point, cell_type)| {
+            simulation.set_cell(point, cell_type.clone());
+            point.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::resources::World;
+
+    #[test]
+    fn step_back_restores_generation() {
+        let level = "6 3 dead\ntrue\ne e e e e e\ne a w w w e\ne e e e e e\n0\n";
+        let mut simulation = Simulation::new(&World::from_string(level.to_owned()).unwrap());
+        let mut stepper = Stepper::default();
+        let start = simulation.clone();
+
+        for ticks in 0..3 {
+            stepper.push(ticks, vec![]);
+            let changed = simulation.step();
+            stepper.record_step(&simulation, &changed);
+        }
+        stepper.record(&Point(0, 0), simulation.get_cell(&Point(0, 0)));
+        simulation.set_cell(&Point(0, 0), WIRE(false));
+
+        while let Some(snapshot) = stepper.pop() {
+            restore(&mut simulation, &snapshot);
+        }
+        for y in 0..3 {
+            for x in 0..6 {
+                assert_eq!(simulation.get_cell(&Point(x, y)), start.get_cell(&Point(x, y)));
+            }
+        }
+
+        simulation.step();
+        assert_eq!(simulation.get_cell(&Point(2, 1)), ELECTRON(false));
+    }
+
+    #[test]
+    fn moves_follow_requests_and_target() {
+        let mut stepper = Stepper { forward: 1, ..Stepper::default() };
+        assert_eq!(stepper.next_move(0), Move::Forward);
+        assert_eq!(stepper.next_move(1), Move::Wait);
+
+        stepper.back = 1;
+        assert_eq!(stepper.next_move(1), Move::Wait);
+
+        stepper.push(0, vec![]);
+        stepper.target = Some(0);
+        assert_eq!(stepper.next_move(1), Move::Back);
+        stepper.pop();
+        assert_eq!(stepper.next_move(0), Move::Wait);
+        assert_eq!(stepper.target, None);
+
+        stepper.target = Some(2);
+        assert_eq!(stepper.next_move(0), Move::Forward);
+    }
+
+    #[test]
+    fn snapshots_are_limited() {
+        let mut stepper = Stepper::default();
+        for ticks in 0..SNAPSHOT_LIMIT + 5 {
+            stepper.push(ticks, vec![]);
+        }
+
+        assert_eq!(stepper.snapshots.len(), SNAPSHOT_LIMIT);
+        assert_eq!(stepper.snapshots.front().map(|snapshot| snapshot.ticks), Some(5));
+    }
+}
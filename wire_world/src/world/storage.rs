@@ -0,0 +1,50 @@
+use crate::world::progress::Progress;
+
+/// Reads the save file next to the assets directory, `None` before the first save.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_progress() -> anyhow::Result<Option<Progress>> {
+    use std::fs;
+    use bevy::asset::FileAssetIo;
+    use crate::world::progress::PROGRESS_FILE;
+
+    let path = FileAssetIo::get_base_path().join(PROGRESS_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(Progress::from_ron(&fs::read_to_string(path)?)?))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_progress(progress: &Progress) -> anyhow::Result<()> {
+    use std::fs;
+    use bevy::asset::FileAssetIo;
+    use crate::world::progress::PROGRESS_FILE;
+
+    fs::write(FileAssetIo::get_base_path().join(PROGRESS_FILE), progress.to_ron()?)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> anyhow::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| anyhow::anyhow!("Local storage isn't available"))
+}
+
+/// Reads the save from the local storage of the browser, `None` before the first save.
+#[cfg(target_arch = "wasm32")]
+pub fn read_progress() -> anyhow::Result<Option<Progress>> {
+    use crate::world::progress::PROGRESS_STORAGE_KEY;
+
+    let saved = local_storage()?.get_item(PROGRESS_STORAGE_KEY)
+        .map_err(|error| anyhow::anyhow!("Failed to read local storage: {:?}", error))?;
+    saved.map(|saved| Progress::from_ron(&saved)).transpose()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_progress(progress: &Progress) -> anyhow::Result<()> {
+    use crate::world::progress::PROGRESS_STORAGE_KEY;
+
+    local_storage()?.set_item(PROGRESS_STORAGE_KEY, &progress.to_ron()?)
+        .map_err(|error| anyhow::anyhow!("Failed to write local storage: {:?}", error))
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use crate::world::clipboard::Pattern;
+use crate::world::components::{CellType, Point};
+use crate::world::components::CellType::{EMPTY, WIRE};
+
+/// How the left mouse button changes the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tool {
+    /// A click toggles a wire, a drag does nothing.
+    #[default]
+    Single,
+    Brush,
+    Line,
+    Rect,
+    Eraser,
+    /// A drag selects a region for copying, it never paints.
+    Select,
+    /// A click attaches a logic analyzer probe to a wire or removes it, it never paints.
+    Probe,
+}
+
+/// Cells covered by a drag with the left button. The whole stroke becomes one edit on release.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    pub start: Point,
+    pub end: Point,
+    pub path: Vec<Point>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Toolbox {
+    pub tool: Tool,
+    pub stroke: Option<Stroke>,
+    /// Opposite corners of the selected region.
+    pub selection: Option<(Point, Point)>,
+    pub clipboard: Option<Pattern>,
+    /// The clipboard follows the cursor and a click pastes it.
+    pub pasting: bool,
+    /// Cell chosen with the keyboard, keys click it like mouse buttons.
+    pub cursor: Option<Point>,
+}
+
+impl Tool {
+    /// New state of a cell painted by the tool, fixed cells are never painted.
+    pub fn paint(&self, cell: &CellType) -> Option<CellType> {
+        if cell.is_fixed() {
+            return None;
+        }
+
+        match self {
+            Tool::Select | Tool::Probe => None,
+            Tool::Eraser => Some(EMPTY(false)),
+            _others => Some(WIRE(false)),
+        }
+    }
+}
+
+impl Toolbox {
+    /// Drops the stroke, the selection and pasting, they refer to cells of the previous board.
+    pub fn forget_board(&mut self) {
+        self.stroke = None;
+        self.selection = None;
+        self.pasting = false;
+        self.cursor = None;
+    }
+}
+
+impl Stroke {
+    pub fn new(point: Point) -> Stroke {
+        Stroke {
+            start: point.clone(),
+            end: point.clone(),
+            path: vec![point],
+        }
+    }
+
+    /// Moves the end of the stroke, the path gets every cell between the old and the new end.
+    pub fn extend(&mut self, point: Point) {
+        if point == self.end {
+            return;
+        }
+
+        self.path.extend(line(&self.end, &point).into_iter().skip(1));
+        self.end = point;
+    }
+
+    pub fn cells(&self, tool: Tool) -> Vec<Point> {
+        match tool {
+            Tool::Line => line(&self.start, &self.end),
+            Tool::Rect | Tool::Select => rect(&self.start, &self.end),
+            _others => self.path.clone(),
+        }
+    }
+}
+
+/// Cells of a straight line, neighbours touch by sides or corners like wires do.
+pub fn line(from: &Point, to: &Point) -> Vec<Point> {
+    let (x0, y0) = (from.0 as isize, from.1 as isize);
+    let (x1, y1) = (to.0 as isize, to.1 as isize);
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+    let mut points = Vec::with_capacity((dx - dy) as usize + 1);
+
+    loop {
+        points.push(Point(x as usize, y as usize));
+        if x == x1 && y == y1 {
+            break;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+
+    points
+}
+
+/// Cells on the border of a rectangle with the given opposite corners.
+pub fn rect(from: &Point, to: &Point) -> Vec<Point> {
+    let (left, right) = (from.0.min(to.0), from.0.max(to.0));
+    let (top, bottom) = (from.1.min(to.1), from.1.max(to.1));
+    let mut points = Vec::new();
+
+    for y in top..=bottom {
+        for x in left..=right {
+            if x == left || x == right || y == top || y == bottom {
+                points.push(Point(x, y));
+            }
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::CellType::ELECTRON;
+
+    #[test]
+    fn line_covers_every_step() {
+        assert_eq!(line(&Point(0, 0), &Point(3, 0)), vec![Point(0, 0), Point(1, 0), Point(2, 0), Point(3, 0)]);
+        assert_eq!(line(&Point(2, 2), &Point(0, 0)), vec![Point(2, 2), Point(1, 1), Point(0, 0)]);
+        assert_eq!(line(&Point(0, 0), &Point(4, 2)).len(), 5);
+    }
+
+    #[test]
+    fn rect_is_outline() {
+        let cells = rect(&Point(2, 2), &Point(0, 0));
+        assert_eq!(cells.len(), 8);
+        assert!(!cells.contains(&Point(1, 1)));
+    }
+
+    #[test]
+    fn stroke_fills_gaps_between_positions() {
+        let mut stroke = Stroke::new(Point(0, 0));
+        stroke.extend(Point(3, 0));
+        stroke.extend(Point(3, 0));
+        stroke.extend(Point(3, 2));
+
+        assert_eq!(stroke.cells(Tool::Brush).len(), 6);
+        assert_eq!(stroke.cells(Tool::Line), line(&Point(0, 0), &Point(3, 2)));
+        assert_eq!(stroke.cells(Tool::Rect).len(), 10);
+    }
+
+    #[test]
+    fn tools_keep_fixed_cells() {
+        assert_eq!(Tool::Brush.paint(&EMPTY(false)), Some(WIRE(false)));
+        assert_eq!(Tool::Eraser.paint(&ELECTRON(false)), Some(EMPTY(false)));
+        assert_eq!(Tool::Brush.paint(&EMPTY(true)), None);
+        assert_eq!(Tool::Eraser.paint(&WIRE(true)), None);
+        assert_eq!(Tool::Select.paint(&EMPTY(false)), None);
+    }
+}
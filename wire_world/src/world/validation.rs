@@ -0,0 +1,107 @@
+use crate::world::components::CellType::WIRE;
+use crate::world::components::Point;
+use crate::world::diagnostics::LevelError;
+use crate::world::resources::World;
+
+impl World {
+    /// Checks a parsed level for mistakes which the parser can't notice: spawns and outputs
+    /// outside the board or off wires and output windows which don't fit the exercise timeout.
+    pub fn validate(&self) -> Vec<LevelError> {
+        let mut errors = Vec::new();
+
+        for (i, exercise) in self.exercises.iter().enumerate() {
+            for (j, (point, _instant)) in exercise.spawns.iter().enumerate() {
+                if let Some(problem) = self.check_wire(point) {
+                    errors.push(LevelError::new(format!("Exercise {i}: electron spawn {j} {problem}")));
+                }
+            }
+
+            for (j, (point, _expectation, from, until)) in exercise.outputs.iter().enumerate() {
+                if let Some(problem) = self.check_wire(point) {
+                    errors.push(LevelError::new(format!("Exercise {i}: output {j} {problem}")));
+                }
+                if from >= until {
+                    errors.push(LevelError::new(format!(
+                        "Exercise {i}: output {j} starts at {from} but ends at {until}"
+                    )));
+                }
+                if *until > exercise.timeout {
+                    errors.push(LevelError::new(format!(
+                        "Exercise {i}: output {j} ends at {until} after timeout {}", exercise.timeout
+                    )));
+                }
+            }
+        }
+
+        errors
+    }
+
+    fn check_wire(&self, point: &Point) -> Option<String> {
+        if point.0 >= self.size.0 || point.1 >= self.size.1 {
+            return Some(format!("at {} x {} is outside the board {} x {}", point.0, point.1, self.size.0, self.size.1));
+        }
+
+        match self.get_cell(point) {
+            WIRE(_) => None,
+            cell => Some(format!("at {} x {} is on '{}' instead of a wire", point.0, point.1, cell.symbol())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::world::resources::World;
+
+    fn level(outputs: &str) -> World {
+        World::from_string(format!("3 1 dead\nfalse\nw w e\n1\nTitle\n\n10\n1\n0 0 0\n1\n{outputs}\n")).unwrap()
+    }
+
+    #[test]
+    fn bundled_levels_are_valid() {
+        for name in ["and", "blocker", "crossroad", "diode", "forward_blocker", "free", "or", "xor"] {
+            let path = format!("{}/assets/{name}.level", env!("CARGO_MANIFEST_DIR"));
+            let content = std::fs::read_to_string(&path).unwrap();
+            let world = World::from_file(Path::new(&path), content).unwrap();
+            assert!(world.validate().is_empty(), "{name}: {:?}", world.validate());
+        }
+    }
+
+    #[test]
+    fn output_on_wire_inside_timeout_is_valid() {
+        assert!(level("true 2 10 1 0").validate().is_empty());
+    }
+
+    #[test]
+    fn report_output_off_wire_and_outside_board() {
+        let errors = level("true 2 10 2 0").validate();
+        assert_eq!(errors[0].message, "Exercise 0: output 0 at 2 x 0 is on 'e' instead of a wire");
+
+        let errors = level("true 2 10 3 0").validate();
+        assert_eq!(errors[0].message, "Exercise 0: output 0 at 3 x 0 is outside the board 3 x 1");
+    }
+
+    #[test]
+    fn report_wrong_output_window() {
+        assert_eq!(level("true 5 5 1 0").validate().len(), 1);
+        assert_eq!(level("true 2 11 1 0").validate().len(), 1);
+    }
+
+    #[test]
+    fn reject_rows_of_wrong_width() {
+        let short = World::from_string("3 2\nfalse\ne e e\ne e\n0\n".to_owned()).unwrap_err();
+        let span = short.span.unwrap();
+        assert_eq!((span.line, span.column), (4, 4));
+        assert_eq!(short.message, "Row 1 has 2 cells but width is 3");
+
+        let long = World::from_string("3 1\nfalse\ne e e e\n0\n".to_owned()).unwrap_err();
+        assert_eq!(long.span.unwrap().column, 7);
+    }
+
+    #[test]
+    fn reject_more_exercises_than_declared() {
+        let error = World::from_string("1 1\nfalse\ne\n0\nTitle\n".to_owned()).unwrap_err();
+        assert_eq!(error.span.unwrap().line, 5);
+    }
+}
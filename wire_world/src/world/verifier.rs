@@ -0,0 +1,211 @@
+use std::fmt::{Display, Formatter};
+
+use crate::world::components::CellType::{ELECTRON, TAIL};
+use crate::world::components::{ExpectedOutput, OutputStatus, Point};
+use crate::world::diagnostics::LevelError;
+use crate::world::resources::World;
+use crate::world::simulation::Simulation;
+
+/// Result of one exercise, `ticks` is the tick when the exercise was passed or failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExerciseReport {
+    pub exercise: usize,
+    pub ticks: usize,
+    pub failure: Option<Failure>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    Output {
+        output: usize,
+        position: Point,
+        expectation: bool,
+    },
+    Timeout(usize),
+}
+
+/// Plays exercises of `level` on the board of `solution` without Bevy, the same way the game does:
+/// the board isn't reset between exercises and verification stops on the first failed exercise.
+pub fn verify(level: &World, solution: &World) -> Result<Vec<ExerciseReport>, LevelError> {
+    check_solution(level, solution)?;
+
+    let mut simulation = Simulation::new(&World {
+        title: level.title.clone(),
+        size: level.size,
+        boundary: level.boundary,
+        map: solution.map.clone(),
+        electron_available: level.electron_available,
+        exercises: vec![],
+    });
+    let mut reports = Vec::with_capacity(level.exercises.len());
+
+    for (id, exercise) in level.exercises.iter().enumerate() {
+        let mut outputs: Vec<ExpectedOutput> = exercise.outputs.iter()
+            .map(|(position, expectation, from, until)| ExpectedOutput {
+                expectation: *expectation,
+                position: position.clone(),
+                from: *from,
+                until: *until,
+                status: OutputStatus::Inactive,
+            })
+            .collect();
+        let mut ticks = 0;
+
+        let report = loop {
+            for output in outputs.iter_mut() {
+                let cell = simulation.get_cell(&output.position);
+                output.update(ticks, &cell);
+            }
+
+            // Same rules as `handle_exercises`: a failure is checked before a success.
+            let failed = outputs.iter().position(|output| output.status == OutputStatus::Fail);
+            if let Some(index) = failed {
+                break ExerciseReport {
+                    exercise: id,
+                    ticks,
+                    failure: Some(Failure::Output {
+                        output: index,
+                        position: outputs[index].position.clone(),
+                        expectation: outputs[index].expectation,
+                    }),
+                };
+            }
+            if ticks > exercise.timeout {
+                break ExerciseReport { exercise: id, ticks, failure: Some(Failure::Timeout(exercise.timeout)) };
+            }
+            if outputs.iter().all(|output| output.status == OutputStatus::Success) {
+                break ExerciseReport { exercise: id, ticks, failure: None };
+            }
+
+            for (position, instant) in exercise.spawns.iter() {
+                if *instant == ticks {
+                    let fixed = simulation.get_cell(position).is_fixed();
+                    simulation.set_cell(position, ELECTRON(fixed));
+                }
+            }
+            simulation.step();
+            ticks += 1;
+        };
+
+        let failed = report.failure.is_some();
+        reports.push(report);
+        if failed {
+            break;
+        }
+    }
+
+    Ok(reports)
+}
+
+/// A solution can change only cells which a player can change in the game.
+fn check_solution(level: &World, solution: &World) -> Result<(), LevelError> {
+    if level.size != solution.size {
+        return Err(LevelError::new(format!(
+            "Solution board is {} x {} but level board is {} x {}",
+            solution.size.0, solution.size.1, level.size.0, level.size.1
+        )));
+    }
+
+    for (index, (original, solved)) in level.map.iter().zip(solution.map.iter()).enumerate() {
+        let point = Point(index % level.size.0, index / level.size.0);
+        if (original.is_fixed() || solved.is_fixed()) && original != solved {
+            return Err(LevelError::new(format!(
+                "Solution changes fixed cell at {} x {}", point.0, point.1
+            )));
+        }
+        if !level.electron_available && original != solved && matches!(solved, ELECTRON(_) | TAIL(_)) {
+            return Err(LevelError::new(format!(
+                "Solution places an electron at {} x {} but the level doesn't allow it", point.0, point.1
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+impl ExerciseReport {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl Display for ExerciseReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.failure {
+            None => write!(f, "exercise {}: passed at tick {}", self.exercise, self.ticks),
+            Some(Failure::Output { output, position, expectation }) => write!(
+                f,
+                "exercise {}: failed at tick {}: output {output} at {} x {} {}",
+                self.exercise,
+                self.ticks,
+                position.0,
+                position.1,
+                match expectation {
+                    true => "didn't receive an electron",
+                    false => "received an unexpected electron",
+                },
+            ),
+            Some(Failure::Timeout(timeout)) => write!(
+                f,
+                "exercise {}: failed at tick {}: timeout {timeout} is exceeded",
+                self.exercise,
+                self.ticks,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::components::CellType::WIRE;
+
+    const LEVEL: &str = "5 1 dead\nfalse\nW e e e W\n1\nDeliver\n\n10\n1\n0 0 0\n1\ntrue 0 8 4 0\n";
+
+    fn world(level: &str) -> World {
+        World::from_string(level.to_owned()).unwrap()
+    }
+
+    #[test]
+    fn connected_wire_passes() {
+        let solution = world(&LEVEL.replace("W e e e W", "W w w w W"));
+        let reports = verify(&world(LEVEL), &solution).unwrap();
+
+        assert_eq!(reports, vec![ExerciseReport { exercise: 0, ticks: 4, failure: None }]);
+    }
+
+    #[test]
+    fn broken_wire_fails_at_end_of_window() {
+        let solution = world(&LEVEL.replace("W e e e W", "W w e w W"));
+        let reports = verify(&world(LEVEL), &solution).unwrap();
+
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].ticks, 8);
+        assert_eq!(reports[0].to_string(), "exercise 0: failed at tick 8: output 0 at 4 x 0 didn't receive an electron");
+    }
+
+    #[test]
+    fn solution_keeps_fixed_cells() {
+        let solution = world(&LEVEL.replace("W e e e W", "w w w w W"));
+        assert!(verify(&world(LEVEL), &solution).is_err());
+
+        let solution = world(&LEVEL.replace("W e e e W", "W a w w W"));
+        assert!(verify(&world(LEVEL), &solution).is_err());
+    }
+
+    #[test]
+    fn diode_has_solution() {
+        let level = world(include_str!("../../assets/diode.level"));
+        let mut solution = world(include_str!("../../assets/diode.level"));
+        let wires = (4..9).chain(10..15).map(|x| Point(x, 4))
+            .chain([Point(9, 3), Point(10, 3), Point(9, 5), Point(10, 5)]);
+        for point in wires {
+            let index = solution.index(&point);
+            solution.map[index] = WIRE(false);
+        }
+
+        let reports = verify(&level, &solution).unwrap();
+        assert!(reports.iter().all(ExerciseReport::passed), "{reports:?}");
+        assert_eq!(reports.len(), 2);
+    }
+}
@@ -1,8 +1,12 @@
 use bevy::asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset};
 
-use crate::world::resources::World;
+use crate::world::resources::{LevelLoadErrors, LevelManifest, World};
 
-pub struct WorldLoader;
+pub struct WorldLoader {
+    pub errors: LevelLoadErrors,
+}
+
+pub struct ManifestLoader;
 
 impl AssetLoader for WorldLoader {
     fn load<'a>(
@@ -11,20 +15,52 @@ impl AssetLoader for WorldLoader {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, anyhow::Result<(), Error>> {
         Box::pin(async move {
+            let path = load_context.path().to_string_lossy().to_string();
             if let Ok(content) = String::from_utf8(Vec::from(bytes)) {
-                let world = World::from_string(content)?;
+                match World::from_file(load_context.path(), content) {
+                    Ok(world) => {
+                        self.errors.remove(&path);
+                        let loaded_asset = LoadedAsset::new(world);
+                        load_context.set_default_asset(loaded_asset);
+                        Ok(())
+                    }
+                    Err(error) => {
+                        self.errors.insert(path, error.to_string());
+                        Err(error.into())
+                    }
+                }
+            } else {
+                self.errors.insert(path, "Failed read level file".to_owned());
+                Err(Error::msg("Failed read level file"))
+            }
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["level", "level.ron"];
+        EXTENSIONS
+    }
+}
 
-                let loaded_asset = LoadedAsset::new(world);
+impl AssetLoader for ManifestLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<(), Error>> {
+        Box::pin(async move {
+            if let Ok(content) = String::from_utf8(Vec::from(bytes)) {
+                let loaded_asset = LoadedAsset::new(LevelManifest::from_string(content));
                 load_context.set_default_asset(loaded_asset);
                 Ok(())
             } else {
-                Err(Error::msg("Failed read level file"))
+                Err(Error::msg("Failed read levels manifest"))
             }
         })
     }
 
     fn extensions(&self) -> &[&str] {
-        static EXTENSIONS: &[&str] = &["level"];
+        static EXTENSIONS: &[&str] = &["manifest"];
         EXTENSIONS
     }
-}
\ No newline at end of file
+}